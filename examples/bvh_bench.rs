@@ -0,0 +1,152 @@
+//! Measures BVH construction and traversal on a scene with many small spheres,
+//! most of them packed into a dense cluster. The tree built with the surface
+//! area heuristic is compared to a tree split at the median along a random
+//! axis, the way BVHNode used to be built. Both trees must report the same
+//! number of hits.
+//!
+//!     cargo run --release --example bvh_bench
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use rsrt::math::Vec3;
+use rsrt::mtl::Lambertian;
+use rsrt::obj::{surrounding_box, Sphere, AABB};
+use rsrt::tex::ConstTexture;
+use rsrt::trace::{BVHNode, Hit, Hittable, Ray};
+use rsrt::utils::rng::{seed, uniform_in_range};
+
+const SPHERES: usize = 20_000;
+const RAYS: usize = 300_000;
+
+fn random_vec(min: f32, max: f32) -> Vec3 {
+    Vec3(
+        uniform_in_range(min, max),
+        uniform_in_range(min, max),
+        uniform_in_range(min, max),
+    )
+}
+
+/// MedianNode is a node of the baseline tree. The elements are sorted along a
+/// random axis and split in half, until one or two remain.
+struct MedianNode {
+    bbox: AABB,
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+}
+
+impl MedianNode {
+    fn new(mut elements: Vec<Arc<dyn Hittable>>, time_begin: f32, time_end: f32) -> MedianNode {
+        let axis = (3.0 * uniform_in_range(0.0, 1.0)) as usize;
+        let key = |h: &Arc<dyn Hittable>| {
+            let min = h.bounding_box(0.0, 0.0).min();
+            match axis {
+                0 => min.0,
+                1 => min.1,
+                _ => min.2,
+            }
+        };
+        elements.sort_by(|h1, h2| key(h1).total_cmp(&key(h2)));
+
+        let (left, right) = match elements.len() {
+            1 => (elements[0].clone(), elements[0].clone()),
+            2 => (elements[0].clone(), elements[1].clone()),
+            len => {
+                let v_right = elements.split_off(len / 2);
+                (
+                    Arc::new(MedianNode::new(elements, time_begin, time_end)) as Arc<dyn Hittable>,
+                    Arc::new(MedianNode::new(v_right, time_begin, time_end)) as Arc<dyn Hittable>,
+                )
+            }
+        };
+
+        let bbox = surrounding_box(
+            left.bounding_box(time_begin, time_end),
+            right.bounding_box(time_begin, time_end),
+        );
+        MedianNode { bbox, left, right }
+    }
+}
+
+impl Hittable for MedianNode {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return None;
+        }
+
+        match (
+            self.left.hit(r, t_min, t_max),
+            self.right.hit(r, t_min, t_max),
+        ) {
+            (None, None) => None,
+            (Some(hit), None) | (None, Some(hit)) => Some(hit),
+            (Some(left_hit), Some(right_hit)) => {
+                if left_hit.t() < right_hit.t() {
+                    Some(left_hit)
+                } else {
+                    Some(right_hit)
+                }
+            }
+        }
+    }
+
+    fn bounding_box(&self, _: f32, _: f32) -> AABB {
+        self.bbox.clone()
+    }
+}
+
+fn main() {
+    seed(1);
+    let elements: Vec<Arc<dyn Hittable>> = (0..SPHERES)
+        .map(|i| {
+            let center = if i % 4 == 0 {
+                random_vec(-100.0, 100.0)
+            } else {
+                random_vec(-5.0, 5.0)
+            };
+            let mat = Lambertian::new(ConstTexture::new(Vec3(0.5, 0.5, 0.5)));
+            Arc::new(Sphere::new(center, 0.05, mat)) as Arc<dyn Hittable>
+        })
+        .collect();
+
+    let start = Instant::now();
+    let median = MedianNode::new(elements.clone(), 0.0, 1.0);
+    println!("median build: {:?}", start.elapsed());
+    let start = Instant::now();
+    let sah = BVHNode::new(elements, 0.0, 1.0);
+    println!("sah build: {:?}", start.elapsed());
+
+    for &(name, spread) in &[("scattered", 1.0), ("cluster", 0.05)] {
+        let median_hits = trace(&median, &format!("median {}", name), spread);
+        let sah_hits = trace(&sah, &format!("sah {}", name), spread);
+        assert_eq!(median_hits, sah_hits, "the trees disagree on {}", name);
+    }
+}
+
+/// Traces rays from a point in front of the cluster, with directions spread
+/// over the given width at unit distance, prints the elapsed time and returns
+/// the number of hits.
+fn trace(bvh: &dyn Hittable, name: &str, spread: f32) -> usize {
+    seed(2);
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..RAYS {
+        let dir = Vec3(
+            uniform_in_range(-spread, spread),
+            uniform_in_range(-spread, spread),
+            -1.5,
+        );
+        let ray = Ray::new(Vec3(0.0, 0.0, 8.0), dir, 0.0);
+        if bvh.hit(&ray, 0.001, f32::MAX).is_some() {
+            hits += 1;
+        }
+    }
+    println!(
+        "trace {}: {:?} ({} rays, {} hits)",
+        name,
+        start.elapsed(),
+        RAYS,
+        hits
+    );
+    hits
+}
@@ -0,0 +1,20 @@
+use crate::background::Background;
+use crate::math::Vec3;
+
+/// ConstBackground is a background with the same color in every direction.
+pub struct ConstBackground {
+    color: Vec3,
+}
+
+impl ConstBackground {
+    /// Returns a background with the given color.
+    pub fn new(color: Vec3) -> ConstBackground {
+        ConstBackground { color }
+    }
+}
+
+impl Background for ConstBackground {
+    fn radiance(&self, _: Vec3) -> Vec3 {
+        self.color
+    }
+}
@@ -0,0 +1,275 @@
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use image::hdr::HDRDecoder;
+use image::{DynamicImage, GenericImageView, ImageResult};
+
+use crate::background::Background;
+use crate::math::Vec3;
+use crate::utils::distribution::Distribution2D;
+use crate::utils::rng::uniform_in_range;
+
+/// EnvMap is a background given by an image in the equirectangular (latitude
+/// and longitude) format. The top row of the image is straight up and its
+/// center column is the -z direction. Radiance HDR images keep their full
+/// range, other images are scaled to [0, 1].
+/// Directions are sampled in proportion to the luminance of the pixels, so the
+/// map can light the scene as a light source.
+///     rotation - angle in degrees by which the map is turned around the y axis
+///     intensity - factor for the colors of the image
+pub struct EnvMap {
+    width: u32,
+    height: u32,
+    pixels: Vec<Vec3>,
+    cos_rot: f32,
+    sin_rot: f32,
+    intensity: f32,
+    distribution: Distribution2D,
+}
+
+impl EnvMap {
+    /// Returns a new environment map for the pixels of an image, stored row by
+    /// row from the top left corner.
+    /// Panics if the number of pixels does not match the resolution.
+    pub fn new(
+        width: u32,
+        height: u32,
+        pixels: Vec<Vec3>,
+        rotation: f32,
+        intensity: f32,
+    ) -> EnvMap {
+        assert_eq!(pixels.len(), (width * height) as usize);
+
+        // rows near the poles cover a smaller solid angle
+        let luminance: Vec<f32> = pixels
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let theta = PI * ((i as u32 / width) as f32 + 0.5) / height as f32;
+                col.luminance() * theta.sin()
+            })
+            .collect();
+        let distribution = Distribution2D::new(&luminance, width as usize, height as usize);
+
+        let rad = rotation.to_radians();
+        EnvMap {
+            width,
+            height,
+            pixels,
+            cos_rot: rad.cos(),
+            sin_rot: rad.sin(),
+            intensity,
+            distribution,
+        }
+    }
+
+    /// Returns a new environment map for an 8-bit image.
+    pub fn from_image(img: &DynamicImage, rotation: f32, intensity: f32) -> EnvMap {
+        let (width, height) = img.dimensions();
+        let pixels = img
+            .to_rgb()
+            .pixels()
+            .map(|p| {
+                Vec3(
+                    p[0] as f32 / 255.0,
+                    p[1] as f32 / 255.0,
+                    p[2] as f32 / 255.0,
+                )
+            })
+            .collect();
+        EnvMap::new(width, height, pixels, rotation, intensity)
+    }
+
+    /// Loads an environment map from an image file. Files with the .hdr
+    /// extension are read as Radiance HDR images.
+    pub fn load<P: AsRef<Path>>(path: P, rotation: f32, intensity: f32) -> ImageResult<EnvMap> {
+        let path = path.as_ref();
+        let is_hdr = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("hdr"));
+        if is_hdr {
+            let reader = BufReader::new(File::open(path)?);
+            read_hdr(reader, rotation, intensity)
+        } else {
+            Ok(EnvMap::from_image(&image::open(path)?, rotation, intensity))
+        }
+    }
+
+    /// Accessor for env_map.width.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Accessor for env_map.height.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the color of the pixel at (x, y), scaled by the intensity.
+    pub fn texel(&self, x: u32, y: u32) -> Vec3 {
+        self.intensity * self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Returns the image coordinates in [0, 1] for the unit direction.
+    pub fn uv(&self, dir: Vec3) -> (f32, f32) {
+        // turn the direction back into the frame of the image
+        let x = self.cos_rot * dir.0 - self.sin_rot * dir.2;
+        let z = self.sin_rot * dir.0 + self.cos_rot * dir.2;
+        let phi = x.atan2(-z);
+        // more precise than acos near the poles
+        let theta = dir.0.hypot(dir.2).atan2(dir.1);
+        (0.5 + phi / (2.0 * PI), theta / PI)
+    }
+
+    /// Returns the unit direction for the image coordinates (u, v).
+    pub fn direction(&self, u: f32, v: f32) -> Vec3 {
+        let phi = (u - 0.5) * 2.0 * PI;
+        let theta = v * PI;
+        let (x, y, z) = (
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        );
+        Vec3(
+            self.cos_rot * x + self.sin_rot * z,
+            y,
+            -self.sin_rot * x + self.cos_rot * z,
+        )
+    }
+}
+
+impl Background for EnvMap {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let (u, v) = self.uv(dir);
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v * self.height as f32) as u32).min(self.height - 1);
+        self.texel(x, y)
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn sample(&self) -> Vec3 {
+        let u1 = uniform_in_range(0.0, 1.0);
+        let u2 = uniform_in_range(0.0, 1.0);
+        let ((u, v), _) = self.distribution.sample(u1, u2);
+        self.direction(u, v)
+    }
+
+    fn pdf(&self, dir: Vec3) -> f32 {
+        let (u, v) = self.uv(dir);
+        let sin_theta = dir.0.hypot(dir.2);
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        // the image covers 2pi x pi radians
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+/// Reads an environment map from a Radiance HDR image.
+fn read_hdr<R: BufRead>(reader: R, rotation: f32, intensity: f32) -> ImageResult<EnvMap> {
+    let decoder = HDRDecoder::new(reader)?;
+    let meta = decoder.metadata();
+    let pixels = decoder
+        .read_image_hdr()?
+        .into_iter()
+        .map(|p| Vec3(p[0], p[1], p[2]))
+        .collect();
+    Ok(EnvMap::new(
+        meta.width,
+        meta.height,
+        pixels,
+        rotation,
+        intensity,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::hdr::HDREncoder;
+    use image::Rgb;
+
+    #[test]
+    fn test_env_map_directions() {
+        let env = EnvMap::new(2, 1, vec![Vec3(0.0, 0.0, 0.0); 2], 30.0, 1.0);
+        for &(u, v) in &[(0.5, 0.5), (0.1, 0.3), (0.8, 0.9)] {
+            let dir = env.direction(u, v);
+            assert!((dir.len() - 1.0).abs() < 1e-5);
+            let (u2, v2) = env.uv(dir);
+            assert!((u - u2).abs() < 1e-4 && (v - v2).abs() < 1e-4);
+        }
+
+        let env = EnvMap::new(2, 1, vec![Vec3(0.0, 0.0, 0.0); 2], 0.0, 1.0);
+        assert!((env.direction(0.5, 0.5) - Vec3(0.0, 0.0, -1.0)).len() < 1e-5);
+        assert!((env.direction(0.5, 0.0) - Vec3(0.0, 1.0, 0.0)).len() < 1e-5);
+    }
+
+    #[test]
+    fn test_env_map_hdr() {
+        // left half dark, right half bright, beyond the range of 8-bit images
+        let data = vec![
+            Rgb([0.25, 0.25, 0.25]),
+            Rgb([8.0, 4.0, 2.0]),
+            Rgb([0.25, 0.25, 0.25]),
+            Rgb([8.0, 4.0, 2.0]),
+        ];
+        let mut buf = Vec::new();
+        HDREncoder::new(&mut buf).encode(&data, 2, 2).unwrap();
+        let env = read_hdr(&buf[..], 0.0, 2.0).unwrap();
+
+        assert_eq!((env.width(), env.height()), (2, 2));
+        // +x is in the right half, -x in the left half
+        assert_eq!(env.radiance(Vec3(1.0, 0.0, 0.0)), Vec3(16.0, 8.0, 4.0));
+        assert_eq!(env.radiance(Vec3(-1.0, 0.0, 0.0)), Vec3(0.5, 0.5, 0.5));
+
+        // turned by 180 degrees, the halves are swapped
+        let env = read_hdr(&buf[..], 180.0, 1.0).unwrap();
+        assert_eq!(env.radiance(Vec3(-1.0, 0.0, 0.0)), Vec3(8.0, 4.0, 2.0));
+    }
+
+    #[test]
+    fn test_env_map_sample() {
+        // a dark map with a single bright pixel
+        let (width, height) = (16, 8);
+        let mut pixels = vec![Vec3(0.1, 0.1, 0.1); width * height];
+        pixels[2 * width + 11] = Vec3(50.0, 50.0, 50.0);
+        let env = EnvMap::new(width as u32, height as u32, pixels, 40.0, 1.0);
+
+        // estimate of the irradiance integral over all directions, which is the
+        // sum of the pixels weighted by their solid angle
+        let expected: f32 = (0..height)
+            .map(|y| {
+                let theta0 = PI * y as f32 / height as f32;
+                let theta1 = PI * (y + 1) as f32 / height as f32;
+                let solid_angle = 2.0 * PI / width as f32 * (theta0.cos() - theta1.cos());
+                (0..width)
+                    .map(|x| env.texel(x as u32, y as u32).0 * solid_angle)
+                    .sum::<f32>()
+            })
+            .sum();
+
+        let n = 20000;
+        let mut mean = 0.0;
+        let mut bright = 0;
+        for _ in 0..n {
+            let dir = env.sample();
+            assert!((dir.len() - 1.0).abs() < 1e-4);
+            let pdf = env.pdf(dir);
+            assert!(pdf > 0.0);
+            let col = env.radiance(dir);
+            if col.0 > 1.0 {
+                bright += 1;
+            }
+            mean += col.0 / pdf / n as f32;
+        }
+        assert!((mean - expected).abs() < 0.05 * expected);
+        // most samples go to the bright pixel
+        assert!(bright > n / 2);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::background::Background;
+use crate::math::Vec3;
+
+/// GradientBackground is a sky that blends linearly from the bottom color,
+/// straight down, to the top color, straight up.
+pub struct GradientBackground {
+    bottom: Vec3,
+    top: Vec3,
+}
+
+impl GradientBackground {
+    /// Returns a new gradient between the given colors.
+    pub fn new(bottom: Vec3, top: Vec3) -> GradientBackground {
+        GradientBackground { bottom, top }
+    }
+}
+
+impl Background for GradientBackground {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let t = 0.5 * (dir.1 + 1.0);
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient() {
+        let sky = GradientBackground::new(Vec3(1.0, 1.0, 1.0), Vec3(0.5, 0.7, 1.0));
+        assert_eq!(sky.radiance(Vec3(0.0, 1.0, 0.0)), Vec3(0.5, 0.7, 1.0));
+        assert_eq!(sky.radiance(Vec3(0.0, -1.0, 0.0)), Vec3(1.0, 1.0, 1.0));
+        assert_eq!(sky.radiance(Vec3(1.0, 0.0, 0.0)), Vec3(0.75, 0.85, 1.0));
+    }
+}
@@ -0,0 +1,11 @@
+pub use constant::ConstBackground;
+pub use env_map::EnvMap;
+pub use gradient::GradientBackground;
+pub use sky::PreethamSky;
+pub use source::Background;
+
+mod constant;
+mod env_map;
+mod gradient;
+mod sky;
+mod source;
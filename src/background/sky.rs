@@ -0,0 +1,264 @@
+use std::f32::consts::PI;
+
+use crate::background::Background;
+use crate::math::{Vec3, ONB};
+use crate::utils::color::xyz_to_rgb;
+use crate::utils::rng::{rand_to_sphere, rand_unit_vector, uniform_in_range};
+
+/// Angular radius of the sun disk in radians.
+const SUN_RADIUS: f32 = 0.0047;
+/// Factor from the luminance of the model in kcd/m² to radiance, chosen such
+/// that a clear sky at noon is about 1.
+const SKY_SCALE: f32 = 1.0 / 15.0;
+/// Irradiance of the sun at normal incidence outside of the atmosphere, in
+/// the units of the sky radiance.
+const SUN_IRRADIANCE: f32 = 25.0;
+/// Probability with which sample chooses the sun disk over the sky.
+const SUN_PROBABILITY: f32 = 0.5;
+
+/// PreethamSky is a daylight sky after the analytic model of Preetham, Shirley
+/// and Smits ("A Practical Analytic Model for Daylight"), together with the sun
+/// disk. The sun is reddened by the atmosphere as it gets lower. Below the
+/// horizon the background is black. Directions are sampled in the sun disk and
+/// over the sky, so the sky can light the scene as a light source.
+///     elevation - angle of the sun above the horizon in degrees
+///     azimuth - angle of the sun around the y axis in degrees, with 0 being
+///               the -z direction and 90 the +x direction
+///     turbidity - haziness of the atmosphere, from 2 (clear) to 10 (hazy)
+///     intensity - factor for the radiance of the sky and the sun
+pub struct PreethamSky {
+    sun_dir: Vec3,
+    sun_radiance: Vec3,
+    cos_sun_max: f32,
+    sun_cone: f32,
+    theta_s: f32,
+    zenith: [f32; 3],
+    perez: [[f32; 5]; 3],
+    intensity: f32,
+}
+
+impl PreethamSky {
+    /// Returns a new sky for the given sun position and turbidity.
+    pub fn new(elevation: f32, azimuth: f32, turbidity: f32, intensity: f32) -> PreethamSky {
+        let (el, az) = (elevation.to_radians(), azimuth.to_radians());
+        let sun_dir = Vec3(el.cos() * az.sin(), el.sin(), -el.cos() * az.cos());
+        let theta_s = (PI / 2.0 - el).clamp(0.0, PI / 2.0);
+        let t = turbidity;
+
+        // distribution coefficients for the luminance Y and the chromaticity x, y
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // values at the zenith
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let lum = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let (th, th2, th3) = (theta_s, theta_s * theta_s, theta_s * theta_s * theta_s);
+        let x = t * t * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let y = t * t * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+
+        // solid angle of the sun disk, 2pi (1 - cos r) written to keep precision
+        let sun_cone = 4.0 * PI * (SUN_RADIUS / 2.0).sin().powi(2);
+        let sun_radiance = if elevation > 0.0 {
+            SUN_IRRADIANCE / sun_cone * sun_transmittance(theta_s, t)
+        } else {
+            Vec3(0.0, 0.0, 0.0)
+        };
+
+        PreethamSky {
+            sun_dir,
+            sun_radiance,
+            cos_sun_max: SUN_RADIUS.cos(),
+            sun_cone,
+            theta_s,
+            zenith: [lum.max(0.0), x, y],
+            perez,
+            intensity,
+        }
+    }
+
+    /// Accessor for sky.sun_dir.
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_dir
+    }
+
+    /// Returns the radiance of the sky without the sun disk.
+    fn sky(&self, dir: Vec3) -> Vec3 {
+        if dir.1 <= 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+        let theta = dir.1.min(1.0).acos();
+        let gamma = dir.dot(self.sun_dir).clamp(-1.0, 1.0).acos();
+
+        let mut xyy = [0.0; 3];
+        for (i, c) in self.perez.iter().enumerate() {
+            xyy[i] = self.zenith[i] * perez(c, theta, gamma) / perez(c, 0.0, self.theta_s);
+        }
+        SKY_SCALE * xyy_to_rgb(xyy[1], xyy[2], xyy[0])
+    }
+
+    fn is_sun_visible(&self) -> bool {
+        self.sun_radiance != Vec3(0.0, 0.0, 0.0)
+    }
+}
+
+impl Background for PreethamSky {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let mut col = self.sky(dir);
+        if dir.dot(self.sun_dir) >= self.cos_sun_max {
+            col = col + self.sun_radiance;
+        }
+        self.intensity * col
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn sample(&self) -> Vec3 {
+        if self.is_sun_visible() && uniform_in_range(0.0, 1.0) < SUN_PROBABILITY {
+            let sin_max = SUN_RADIUS.sin();
+            return ONB::from_w(self.sun_dir).local(rand_to_sphere(sin_max, 1.0));
+        }
+
+        // uniformly over the upper hemisphere
+        let dir = rand_unit_vector();
+        Vec3(dir.0, dir.1.abs(), dir.2)
+    }
+
+    fn pdf(&self, dir: Vec3) -> f32 {
+        let p_sun = if self.is_sun_visible() {
+            SUN_PROBABILITY
+        } else {
+            0.0
+        };
+
+        let mut pdf = 0.0;
+        if dir.1 >= 0.0 {
+            pdf += (1.0 - p_sun) / (2.0 * PI);
+        }
+        if dir.dot(self.sun_dir) >= self.cos_sun_max {
+            pdf += p_sun / self.sun_cone;
+        }
+        pdf
+    }
+}
+
+/// Returns the Perez distribution function for the coefficients, at the angle
+/// theta to the zenith and the angle gamma to the sun.
+fn perez(c: &[f32; 5], theta: f32, gamma: f32) -> f32 {
+    let cos_theta = theta.cos().max(0.01);
+    let cos_gamma = gamma.cos();
+    (1.0 + c[0] * (c[1] / cos_theta).exp())
+        * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * cos_gamma * cos_gamma)
+}
+
+/// Returns the linear sRGB color for the chromaticity x, y and luminance lum.
+fn xyy_to_rgb(x: f32, y: f32, lum: f32) -> Vec3 {
+    if y <= 0.0 {
+        return Vec3(0.0, 0.0, 0.0);
+    }
+    let rgb = xyz_to_rgb(Vec3(x / y * lum, lum, (1.0 - x - y) / y * lum));
+    Vec3(rgb.0.max(0.0), rgb.1.max(0.0), rgb.2.max(0.0))
+}
+
+/// Returns the fraction of the sunlight at red, green and blue wavelengths that
+/// passes through the atmosphere for the sun at the zenith angle theta_s, due
+/// to Rayleigh and aerosol scattering.
+fn sun_transmittance(theta_s: f32, turbidity: f32) -> Vec3 {
+    // relative optical mass of the air (Kasten and Young)
+    let deg = theta_s.to_degrees();
+    let m = 1.0 / (theta_s.cos() + 0.50572 * (96.07995 - deg).powf(-1.6364));
+    let beta = 0.04608 * turbidity - 0.04586;
+
+    // wavelengths in micrometers
+    let channel = |lambda: f32| {
+        let rayleigh = (-0.008735 * lambda.powf(-4.08) * m).exp();
+        let aerosol = (-beta * lambda.powf(-1.3) * m).exp();
+        rayleigh * aerosol
+    };
+    Vec3(channel(0.68), channel(0.55), channel(0.44))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sky_radiance() {
+        let sky = PreethamSky::new(45.0, 90.0, 3.0, 1.0);
+        assert!((sky.sun_direction() - Vec3(0.5f32.sqrt(), 0.5f32.sqrt(), 0.0)).len() < 1e-5);
+
+        // the sky is blue above and brighter near the sun, the ground is black
+        let zenith = sky.radiance(Vec3(0.0, 1.0, 0.0));
+        assert!(zenith.2 > zenith.0 && zenith.0 > 0.0);
+        let near_sun = sky.radiance(Vec3(1.0, 1.2, 0.0).as_unit());
+        let away = sky.radiance(Vec3(-1.0, 1.2, 0.0).as_unit());
+        assert!(near_sun.1 > away.1);
+        assert_eq!(sky.radiance(Vec3(0.0, -1.0, 0.0)), Vec3(0.0, 0.0, 0.0));
+
+        // the sun is much brighter than the sky and redder when it is low
+        let sun = sky.radiance(sky.sun_direction());
+        assert!(sun.1 > 1000.0 * zenith.1);
+        let low = PreethamSky::new(5.0, 90.0, 3.0, 1.0);
+        let low_sun = low.radiance(low.sun_direction());
+        assert!(low_sun.2 / low_sun.0 < sun.2 / sun.0);
+    }
+
+    #[test]
+    fn test_sky_sample() {
+        let sky = PreethamSky::new(30.0, 20.0, 4.0, 1.0);
+
+        // sun plus numeric integral of the sky over the upper hemisphere
+        let (nt, np) = (200, 400);
+        let mut expected = sky.sun_radiance.1 * sky.sun_cone;
+        for i in 0..nt {
+            for j in 0..np {
+                let theta = (i as f32 + 0.5) / nt as f32 * PI / 2.0;
+                let phi = (j as f32 + 0.5) / np as f32 * 2.0 * PI;
+                let dir = Vec3(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                let d_omega = theta.sin() * (PI / 2.0 / nt as f32) * (2.0 * PI / np as f32);
+                expected += sky.sky(dir).1 * d_omega;
+            }
+        }
+
+        let n = 40000;
+        let mut mean = 0.0;
+        for _ in 0..n {
+            let dir = sky.sample();
+            assert!((dir.len() - 1.0).abs() < 1e-4);
+            let pdf = sky.pdf(dir);
+            assert!(pdf > 0.0);
+            mean += sky.radiance(dir).1 / pdf / n as f32;
+        }
+        assert!((mean - expected).abs() < 0.03 * expected);
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use crate::math::Vec3;
+
+/// Background is a trait for the light that arrives from outside of the scene,
+/// i.e. the color of rays that do not hit any object. Backgrounds are shared
+/// between the render threads, so they must be Send and Sync.
+pub trait Background: Send + Sync {
+    /// Returns the radiance arriving from the given unit direction.
+    fn radiance(&self, dir: Vec3) -> Vec3;
+
+    /// Returns whether directions of the background can be sampled with
+    /// sample and pdf, so that it can be used as a light source.
+    fn can_sample(&self) -> bool {
+        false
+    }
+
+    /// Returns a random unit direction, preferring directions with more
+    /// radiance, for backgrounds that can be sampled.
+    fn sample(&self) -> Vec3 {
+        Vec3(0.0, 1.0, 0.0)
+    }
+
+    /// Returns the density, with respect to solid angle, with which sample
+    /// returns the unit direction. It is 0 for backgrounds that can not be
+    /// sampled.
+    fn pdf(&self, _dir: Vec3) -> f32 {
+        0.0
+    }
+}
+
+impl<B: Background + ?Sized> Background for Arc<B> {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        self.as_ref().radiance(dir)
+    }
+
+    fn can_sample(&self) -> bool {
+        self.as_ref().can_sample()
+    }
+
+    fn sample(&self) -> Vec3 {
+        self.as_ref().sample()
+    }
+
+    fn pdf(&self, dir: Vec3) -> f32 {
+        self.as_ref().pdf(dir)
+    }
+}
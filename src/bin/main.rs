@@ -1,136 +1,323 @@
 extern crate rsrt;
 
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-
-use threadpool::ThreadPool;
-
-use rsrt::math::Vec3;
-use rsrt::mtl::{Dielectric, Lambertian, LightDiffuse, Metal};
-use rsrt::obj::transform::{FlipNormals, RotateY, Translate};
-use rsrt::obj::{ConstDensity, MovSphere, RectBox, Sphere, XYRect, XZRect, YZRect};
-use rsrt::strategy::Bucket;
-use rsrt::tex::{CheckerTexture, ConstTexture, ImageTexture};
-use rsrt::trace::{BVHNode, Camera, HitVec, Hittable, Ray};
-use rsrt::utils::rng::uniform_in_range;
-use std::rc::Rc;
-
-fn main() -> Result<(), std::io::Error> {
-    let nx = 1000;
-    let ny = 1000;
-    let ns = 100;
-    let nthreshold = 0.0001;
-    let strategy = Bucket::new(nx, ny, 4);
-
-    let (cam, hit_vec) = prepare_scene(nx, ny);
-
-    let cam = Arc::new(cam);
-    let hit_vec = Arc::new(hit_vec);
-    let pool = ThreadPool::new(3);
-
-    let pixels_data = Arc::new(Mutex::new(HashMap::new()));
-
-    let mut buf = image::ImageBuffer::new(nx, ny);
-
-    for items in strategy {
-        let cam = Arc::clone(&cam);
-        let hit_vec = Arc::clone(&hit_vec);
-        let pixels_data = pixels_data.clone();
-
-        pool.execute(move || {
-            for (y, x) in items {
-                let u = (x as f32 + uniform_in_range(0.0, 1.0)) / nx as f32;
-                let v = ((ny - y) as f32 + uniform_in_range(0.0, 1.0)) / ny as f32;
-                let mut col = Vec3(0.0, 0.0, 0.0);
-                let mut i = 0;
-                let iters = loop {
-                    let newcol = col + color(cam.get_ray(u, v), &hit_vec, 0);
-                    if (newcol.0 - col.0).abs() < nthreshold && (newcol.1 - col.1).abs() < nthreshold && (newcol.2 - col.2) < nthreshold {
-                        // noise threshold reached
-                        break i;
-                    }
-                    col = newcol;
-                    if i == ns-1 {
-                        break i;
-                    }
-                    i+=1;
-                };
-                col = col / (iters+1) as f32;
-                col = Vec3(
-                    col.0.sqrt().min(1.0),
-                    col.1.sqrt().min(1.0),
-                    col.2.sqrt().min(1.0),
-                );
-
-                let mut pixels = pixels_data.lock().unwrap();
-                pixels.insert((x, y), col);
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use image::ImageFormat;
+
+use rsrt::integrator::{AOIntegrator, Integrator, NormalIntegrator, PathIntegrator};
+use rsrt::render::{RenderSettings, Renderer};
+use rsrt::scene;
+use rsrt::trace::Hittable;
+
+const USAGE: &str = "\
+Usage: rsrt-cli [OPTIONS] <SCENE>
+
+Renders the scene description from the file SCENE.
+
+Options:
+  -W, --width <N>        width of the image in pixels [default: 1000]
+  -H, --height <N>       height of the image in pixels [default: 1000]
+  -s, --samples <N>      maximum number of samples per pixel [default: 100]
+      --min-samples <N>  number of samples per pixel before the noise
+                         threshold is checked [default: 16]
+      --noise-threshold <X>
+                         sampling of a pixel stops once a new sample changes
+                         its average by less than X [default: 0.0001]
+  -d, --max-depth <N>    maximum number of ray bounces [default: 50]
+      --rr-depth <N>     number of bounces after which paths may be
+                         terminated by Russian roulette [default: 5]
+  -i, --integrator <NAME>
+                         light transport algorithm [default: path]:
+                           path     path tracing
+                           ao       ambient occlusion
+                           normals  surface normals of the first hit
+  -t, --threads <N>      number of worker threads [default: number of CPUs]
+  -b, --buckets <N>      number of buckets the image is split into
+                         [default: 4 per thread]
+  -o, --output <PATH>    path of the output image [default: out.png]
+  -f, --format <FORMAT>  format of the output image: png, jpeg, bmp or tiff
+                         [default: guessed from the output path]
+      --seed <N>         seed for the random generators, for reproducible
+                         renders
+  -h, --help             print this help and exit
+";
+
+/// Options holds everything needed for a single rsrt-cli run.
+#[derive(Debug)]
+struct Options {
+    settings: RenderSettings,
+    integrator: IntegratorKind,
+    scene: PathBuf,
+    output: PathBuf,
+    format: ImageFormat,
+}
+
+#[derive(Debug, PartialEq)]
+enum IntegratorKind {
+    Path,
+    AO,
+    Normals,
+}
+
+#[derive(Debug)]
+enum Command {
+    Render(Options),
+    Help,
+}
+
+fn main() {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("rsrt-cli: {}", err);
+            eprintln!("Try 'rsrt-cli --help' for more information.");
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Help => print!("{}", USAGE),
+        Command::Render(opts) => {
+            if let Err(err) = render(opts) {
+                eprintln!("rsrt-cli: {}", err);
+                std::process::exit(1);
             }
-        });
+        }
     }
+}
+
+fn render(opts: Options) -> Result<(), String> {
+    let Options {
+        settings,
+        integrator,
+        scene,
+        output,
+        format,
+    } = opts;
 
-    pool.join();
+    let aspect = settings.width as f32 / settings.height as f32;
+    let scene =
+        scene::load(&scene, aspect).map_err(|err| format!("{}: {}", scene.display(), err))?;
 
-    let pixels_data = pixels_data.lock().unwrap();
-    for (x, y, pixel) in buf.enumerate_pixels_mut() {
-        let col = pixels_data
-            .get(&(x, y))
-            .unwrap_or_else(|| panic!("missing {}, {} in params", x, y));
+    let integrator: Arc<dyn Integrator> = match integrator {
+        IntegratorKind::Path => {
+            Arc::new(PathIntegrator::new(settings.max_depth, settings.rr_depth))
+        }
+        IntegratorKind::AO => {
+            // occluders are searched within a tenth of the scene's size
+            let bbox = scene.world.bounding_box(0.0, 1.0);
+            Arc::new(AOIntegrator::new(0.1 * (bbox.max() - bbox.min()).len()))
+        }
+        IntegratorKind::Normals => Arc::new(NormalIntegrator::new()),
+    };
+
+    let renderer = Renderer::new(scene, integrator, settings);
+    let fb = renderer.render();
+
+    fb.save(&output, format)
+        .map_err(|err| format!("{}: {}", output.display(), err))
+}
 
-        let r = (255.99 * col.0) as u8;
-        let g = (255.99 * col.1) as u8;
-        let b = (255.99 * col.2) as u8;
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut settings = RenderSettings {
+        threads: std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        ..RenderSettings::default()
+    };
+    let mut integrator = IntegratorKind::Path;
+    let mut buckets = None;
+    let mut scene = None;
+    let mut output = PathBuf::from("out.png");
+    let mut format = None;
 
-        *pixel = image::Rgb([r, g, b]);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            if scene.is_some() {
+                return Err(format!("unexpected argument '{}'", arg));
+            }
+            scene = Some(PathBuf::from(arg));
+            continue;
+        }
+
+        // both "--name value" and "--name=value" are accepted
+        let (name, inline) = match arg.find('=') {
+            Some(idx) if arg.starts_with("--") => {
+                (arg[..idx].to_string(), Some(arg[idx + 1..].to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = || -> Result<String, String> {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("option '{}' requires a value", name))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-W" | "--width" => settings.width = positive(&name, &value()?)?,
+            "-H" | "--height" => settings.height = positive(&name, &value()?)?,
+            "-s" | "--samples" => settings.samples = positive(&name, &value()?)?,
+            "--min-samples" => settings.min_samples = number(&name, &value()?)?,
+            "--noise-threshold" => settings.noise_threshold = number(&name, &value()?)?,
+            "-d" | "--max-depth" => settings.max_depth = number(&name, &value()?)?,
+            "--rr-depth" => settings.rr_depth = number(&name, &value()?)?,
+            "-i" | "--integrator" => integrator = parse_integrator(&value()?)?,
+            "-t" | "--threads" => settings.threads = positive(&name, &value()?)?,
+            "-b" | "--buckets" => buckets = Some(positive(&name, &value()?)?),
+            "-o" | "--output" => output = PathBuf::from(value()?),
+            "-f" | "--format" => format = Some(parse_format(&value()?)?),
+            "--seed" => settings.seed = Some(number(&name, &value()?)?),
+            _ => return Err(format!("unknown option '{}'", name)),
+        }
     }
 
-    buf.save_with_format(
-        "c://Users/User/Desktop/watery_-1.jpeg",
-        image::ImageFormat::JPEG,
-    )
+    let scene = scene.ok_or_else(|| "missing scene file".to_string())?;
+    settings.buckets = buckets.unwrap_or(4 * settings.threads as u32);
+    let format = match format {
+        Some(format) => format,
+        None => {
+            let ext = output
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default();
+            parse_format(ext).map_err(|_| {
+                format!(
+                    "cannot guess the image format of '{}', use --format",
+                    output.display()
+                )
+            })?
+        }
+    };
+
+    Ok(Command::Render(Options {
+        settings,
+        integrator,
+        scene,
+        output,
+        format,
+    }))
 }
 
-fn prepare_scene(nx: u32, ny: u32) -> (Camera, HitVec) {
-    let lookfrom = Vec3(50.0, 0.0, 0.0);
-    let lookat = Vec3(0.0, 0.0, 0.0);
-    let focus_dist = 4.0;
-    let aperture = 0.0;
-    let cam = Camera::new(
-        lookfrom,
-        lookat,
-        Vec3(0.0, 1.0, 0.0),
-        4.0,
-        nx as f32 / ny as f32,
-        aperture,
-        focus_dist,
-        0.0,
-        1.0,
-    );
-
-    let objects: Vec<Box<dyn Hittable>> = vec![
-        Box::new(Sphere::new(Vec3(-6.0, 0.0, 0.0), 1.0, Lambertian::new(ConstTexture::new(Vec3(0.8, 0.1, 0.1))))),
-        Box::new(Sphere::new(Vec3(-5.0, -1.0, 0.0), 1.0, Dielectric::new(-1.33, ConstTexture::new(Vec3(0.1, 0.4, 0.4))))),
-    ];
-
-    (cam, HitVec::new(objects))
+fn number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for option '{}'", value, name))
 }
 
-fn compute_background(_: &Ray) -> Vec3 {
-    Vec3(0.7, 0.7, 0.7)
+fn positive<T: FromStr + Default + PartialEq>(name: &str, value: &str) -> Result<T, String> {
+    let n = number(name, value)?;
+    if n == T::default() {
+        return Err(format!("option '{}' must be greater than 0", name));
+    }
+    Ok(n)
 }
 
-fn color(r: Ray, hit_vec: &HitVec, depth: u8) -> Vec3 {
-    if let Some(hit) = hit_vec.hit(&r, 0.001, std::f32::MAX) {
-        let emitted = hit.mat_ref().emitted(hit.u(), hit.v(), hit.p());
-        if depth > 50 {
-            return emitted;
-        }
+fn parse_integrator(name: &str) -> Result<IntegratorKind, String> {
+    match name {
+        "path" => Ok(IntegratorKind::Path),
+        "ao" => Ok(IntegratorKind::AO),
+        "normals" => Ok(IntegratorKind::Normals),
+        _ => Err(format!("unknown integrator '{}'", name)),
+    }
+}
 
-        if let Some((r, col)) = hit.scatter(&r) {
-            return emitted + col * color(r, hit_vec, depth + 1);
-        } else {
-            return emitted;
-        }
+fn parse_format(format: &str) -> Result<ImageFormat, String> {
+    match format.to_ascii_lowercase().as_str() {
+        "png" => Ok(ImageFormat::PNG),
+        "jpg" | "jpeg" => Ok(ImageFormat::JPEG),
+        "bmp" => Ok(ImageFormat::BMP),
+        "tif" | "tiff" => Ok(ImageFormat::TIFF),
+        _ => Err(format!("unsupported image format '{}'", format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
     }
 
-    compute_background(&r)
+    #[test]
+    fn test_parse_args() {
+        let cmd = parse(&[
+            "-W",
+            "320",
+            "--height=240",
+            "-s",
+            "16",
+            "--min-samples=4",
+            "--noise-threshold",
+            "0.01",
+            "--max-depth",
+            "8",
+            "--rr-depth=3",
+            "-i",
+            "ao",
+            "-t",
+            "2",
+            "-o",
+            "out/cornell.jpg",
+            "--seed",
+            "7",
+            "cornell.rsrt",
+        ]);
+        let opts = match cmd {
+            Ok(Command::Render(opts)) => opts,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        assert_eq!(opts.settings.width, 320);
+        assert_eq!(opts.settings.height, 240);
+        assert_eq!(opts.settings.samples, 16);
+        assert_eq!(opts.settings.min_samples, 4);
+        assert_eq!(opts.settings.noise_threshold, 0.01);
+        assert_eq!(opts.settings.max_depth, 8);
+        assert_eq!(opts.settings.rr_depth, 3);
+        assert_eq!(opts.integrator, IntegratorKind::AO);
+        assert_eq!(opts.settings.threads, 2);
+        assert_eq!(opts.settings.buckets, 8);
+        assert_eq!(opts.settings.seed, Some(7));
+        assert_eq!(opts.scene, PathBuf::from("cornell.rsrt"));
+        assert_eq!(opts.output, PathBuf::from("out/cornell.jpg"));
+        assert_eq!(opts.format, ImageFormat::JPEG);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(matches!(
+            parse(&["--help", "scene.rsrt"]),
+            Ok(Command::Help)
+        ));
+        assert_eq!(parse(&[]).unwrap_err(), "missing scene file");
+        assert_eq!(
+            parse(&["-s", "0", "a.rsrt"]).unwrap_err(),
+            "option '-s' must be greater than 0"
+        );
+        assert_eq!(
+            parse(&["--width", "wide", "a.rsrt"]).unwrap_err(),
+            "invalid value 'wide' for option '--width'"
+        );
+        assert_eq!(
+            parse(&["a.rsrt", "-o"]).unwrap_err(),
+            "option '-o' requires a value"
+        );
+        assert_eq!(
+            parse(&["-o", "out.gif", "a.rsrt"]).unwrap_err(),
+            "cannot guess the image format of 'out.gif', use --format"
+        );
+        assert_eq!(
+            parse(&["--integrator=whitted", "a.rsrt"]).unwrap_err(),
+            "unknown integrator 'whitted'"
+        );
+        assert_eq!(
+            parse(&["--verbose", "a.rsrt"]).unwrap_err(),
+            "unknown option '--verbose'"
+        );
+    }
 }
@@ -0,0 +1,67 @@
+use crate::integrator::Integrator;
+use crate::math::{Vec3, ONB};
+use crate::scene::Scene;
+use crate::trace::Ray;
+use crate::utils::rng::rand_cosine_direction;
+
+/// AOIntegrator renders ambient occlusion. For every hit a single ray is cast in a
+/// cosine-distributed direction around the normal, and the hit is white if the ray
+/// does not hit anything within the given distance and black otherwise. Averaged
+/// over many samples, this shows how much each point is enclosed by the scene.
+pub struct AOIntegrator {
+    distance: f32,
+}
+
+impl AOIntegrator {
+    /// Returns a new ambient occlusion integrator. Objects farther than distance
+    /// do not occlude.
+    pub fn new(distance: f32) -> AOIntegrator {
+        AOIntegrator { distance }
+    }
+}
+
+impl Integrator for AOIntegrator {
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3 {
+        let hit = match scene.world.hit(r, 0.001, f32::MAX) {
+            Some(hit) => hit,
+            None => return scene.background.radiance(r.direction().as_unit()),
+        };
+
+        // occlusion is computed on the side of the surface the ray came from
+        let n = if hit.ng().dot(r.direction()) > 0.0 {
+            -hit.n()
+        } else {
+            hit.n()
+        };
+        let occlusion_ray = Ray::new(hit.p(), cosine_direction(n), r.time());
+        match scene.world.hit(&occlusion_ray, 0.001, self.distance) {
+            Some(_) => Vec3(0.0, 0.0, 0.0),
+            None => Vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Returns a random unit vector in the hemisphere around the unit vector n, with
+/// density proportional to the cosine of its angle to n.
+fn cosine_direction(n: Vec3) -> Vec3 {
+    ONB::from_w(n).local(rand_cosine_direction())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_direction() {
+        let n = Vec3(0.0, 0.6, 0.8);
+        let mut mean_cos = 0.0;
+        for _ in 0..10000 {
+            let dir = cosine_direction(n);
+            assert!((dir.len() - 1.0).abs() < 1e-4);
+            assert!(dir.dot(n) >= 0.0);
+            mean_cos += dir.dot(n) / 10000.0;
+        }
+        // E[cos] = 2/3 for the cosine distribution
+        assert!((mean_cos - 2.0 / 3.0).abs() < 0.02);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::math::Vec3;
+use crate::mtl::Medium;
+
+/// MediumStack holds the media of the dielectric objects that enclose the
+/// current point of a path, in the order in which they were entered. Where
+/// objects overlap, the medium with the highest priority fills the space, so
+/// the interfaces of the others are skipped (nested dielectrics). Outside of
+/// all objects is air with index 1 that does not absorb light.
+#[derive(Clone, Default)]
+pub(crate) struct MediumStack {
+    media: Vec<Medium>,
+}
+
+impl MediumStack {
+    pub fn new() -> MediumStack {
+        MediumStack::default()
+    }
+
+    /// Returns the medium that fills the space at the current point, the last
+    /// entered one among those with the highest priority, leaving out the one
+    /// at index skip.
+    fn current_without(&self, skip: Option<usize>) -> Option<&Medium> {
+        self.media
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != skip)
+            .fold(None, |cur: Option<&Medium>, (_, m)| match cur {
+                Some(cur) if cur.priority > m.priority => Some(cur),
+                _ => Some(m),
+            })
+    }
+
+    /// Returns the index of the last entered medium equal to medium.
+    fn position(&self, medium: &Medium) -> Option<usize> {
+        self.media.iter().rposition(|m| m == medium)
+    }
+
+    /// Returns the index of refraction on the other side of the interface of
+    /// an object with the medium, which the path is entering or leaving, or
+    /// None if the interface lies inside a medium of higher priority and does
+    /// not scatter.
+    pub fn outside_ior(&self, medium: &Medium, entering: bool) -> Option<f32> {
+        let outside = if entering {
+            self.current_without(None)
+        } else {
+            self.current_without(self.position(medium))
+        };
+        match outside {
+            Some(m) if m.priority > medium.priority => None,
+            Some(m) => Some(m.ior),
+            None => Some(1.0),
+        }
+    }
+
+    /// Updates the stack for a path that crosses the interface of an object
+    /// with the medium.
+    pub fn cross(&mut self, medium: Medium, entering: bool) {
+        if entering {
+            self.media.push(medium);
+        } else if let Some(i) = self.position(&medium) {
+            self.media.remove(i);
+        }
+    }
+
+    /// Returns the fraction of light that is not absorbed after travelling
+    /// the distance dist from the current point.
+    pub fn transmittance(&self, dist: f32) -> Vec3 {
+        match self.current_without(None) {
+            Some(m) => m.transmittance(dist),
+            None => Vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_media() {
+        let glass = Medium::new(1.5, Vec3(0.0, 0.0, 0.0), 2);
+        let water = Medium::new(1.33, Vec3(0.1, 0.1, 0.1), 1);
+        let mut stack = MediumStack::new();
+
+        // air to glass
+        assert_eq!(stack.outside_ior(&glass, true), Some(1.0));
+        stack.cross(glass, true);
+        // the surface of the water inside the glass wall is skipped
+        assert_eq!(stack.outside_ior(&water, true), None);
+        stack.cross(water, true);
+        // glass to water
+        assert_eq!(stack.outside_ior(&glass, false), Some(1.33));
+        stack.cross(glass, false);
+        assert_eq!(
+            stack.transmittance(10.0),
+            Vec3(1.0, 1.0, 1.0) * (-1.0f32).exp()
+        );
+        // water to air
+        assert_eq!(stack.outside_ior(&water, false), Some(1.0));
+        stack.cross(water, false);
+        assert_eq!(stack.transmittance(10.0), Vec3(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_equal_priorities() {
+        // ice floating in water
+        let water = Medium::new(1.33, Vec3(0.0, 0.0, 0.0), 0);
+        let ice = Medium::new(1.31, Vec3(0.0, 0.0, 0.0), 0);
+        let mut stack = MediumStack::new();
+
+        stack.cross(water, true);
+        assert_eq!(stack.outside_ior(&ice, true), Some(1.33));
+        stack.cross(ice, true);
+        assert_eq!(stack.outside_ior(&ice, false), Some(1.33));
+        stack.cross(ice, false);
+        assert_eq!(stack.outside_ior(&water, false), Some(1.0));
+
+        // leaving a medium that was never entered, e.g. a single face
+        assert_eq!(stack.outside_ior(&ice, false), Some(1.33));
+    }
+}
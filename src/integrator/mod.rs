@@ -0,0 +1,10 @@
+pub use ao::AOIntegrator;
+pub use normal::NormalIntegrator;
+pub use path::PathIntegrator;
+pub use transport::Integrator;
+
+mod ao;
+mod medium;
+mod normal;
+mod path;
+mod transport;
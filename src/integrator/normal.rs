@@ -0,0 +1,31 @@
+use crate::integrator::Integrator;
+use crate::math::Vec3;
+use crate::scene::Scene;
+use crate::trace::Ray;
+
+/// NormalIntegrator is a debug view that shows the surface normals of the first
+/// hit, mapped from [-1, 1] to [0, 1] per component. Rays that leave the scene are
+/// black.
+pub struct NormalIntegrator {}
+
+impl NormalIntegrator {
+    /// Returns a new normal debug view.
+    pub fn new() -> NormalIntegrator {
+        NormalIntegrator {}
+    }
+}
+
+impl Default for NormalIntegrator {
+    fn default() -> NormalIntegrator {
+        NormalIntegrator::new()
+    }
+}
+
+impl Integrator for NormalIntegrator {
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3 {
+        match scene.world.hit(r, 0.001, f32::MAX) {
+            Some(hit) => 0.5 * (hit.n() + Vec3(1.0, 1.0, 1.0)),
+            None => Vec3(0.0, 0.0, 0.0),
+        }
+    }
+}
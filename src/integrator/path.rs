@@ -0,0 +1,249 @@
+use crate::integrator::medium::MediumStack;
+use crate::integrator::Integrator;
+use crate::math::Vec3;
+use crate::mtl::power_heuristic;
+use crate::scene::Scene;
+use crate::trace::{Hit, Ray};
+use crate::utils::rng::uniform_in_range;
+
+/// PathIntegrator is a path tracer. Rays are scattered by the materials they hit
+/// until they leave the scene or reach the maximum number of bounces, and the
+/// emitted light along the path is attenuated by every scattering.
+/// At hits on materials that do not scatter specularly, one light source of the
+/// scene is sampled directly (next event estimation). Both the light sample and
+/// the light that the scattered ray finds on an emitting object are weighted
+/// with the power heuristic (multiple importance sampling). Point, spot and
+/// directional lights are only found by sampling them.
+/// Paths keep track of the media of the dielectric objects they are inside of,
+/// which absorb light along the way and give the index of refraction on the
+/// other side of interfaces between two dielectrics.
+pub struct PathIntegrator {
+    max_depth: u32,
+    rr_depth: u32,
+}
+
+impl PathIntegrator {
+    /// Returns a new path tracer that follows rays for up to max_depth bounces.
+    /// After rr_depth bounces, paths are terminated randomly with a probability
+    /// that grows as their throughput gets smaller (Russian roulette).
+    pub fn new(max_depth: u32, rr_depth: u32) -> PathIntegrator {
+        PathIntegrator {
+            max_depth,
+            rr_depth,
+        }
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3 {
+        let mut col = Vec3(0.0, 0.0, 0.0);
+        let mut throughput = Vec3(1.0, 1.0, 1.0);
+        let mut ray = r.clone();
+        // density of the last scattering, if the lights were sampled as well
+        let mut bsdf_pdf = None;
+        let mut media = MediumStack::new();
+        // the ray continues beyond t_start after interfaces it passes through
+        let mut t_start = 0.0;
+        let mut depth = 0;
+
+        loop {
+            let mut hit = match scene.world.hit(&ray, t_start + 0.001, f32::MAX) {
+                Some(hit) => hit,
+                None => {
+                    let mut background = scene.background.radiance(ray.direction().as_unit());
+                    if let Some(pdf) = bsdf_pdf {
+                        if scene.background.can_sample() {
+                            let light_pdf = scene.lights.infinite_pdf(&ray);
+                            background = background * power_heuristic(pdf, light_pdf);
+                        }
+                    }
+                    // a ray that escapes from inside a medium travels through it
+                    // forever, so only the channels it does not absorb remain
+                    let transmittance = media.transmittance(f32::INFINITY);
+                    col = col + throughput * transmittance * background;
+                    break;
+                }
+            };
+
+            let dist = (hit.t() - t_start) * ray.direction().len();
+            throughput = throughput * media.transmittance(dist);
+
+            let mat = hit.mat_ref();
+            let entering = ray.direction().dot(hit.ng()) < 0.0;
+            if let Some(medium) = mat.medium() {
+                match media.outside_ior(&medium, entering) {
+                    Some(ior) => hit.set_outside_ior(ior),
+                    None => {
+                        // the medium of higher priority continues across the interface
+                        media.cross(medium, entering);
+                        t_start = hit.t();
+                        continue;
+                    }
+                }
+            }
+
+            let mut emitted = mat.emitted(&ray, &hit);
+            if let Some(pdf) = bsdf_pdf {
+                if mat.is_emissive() {
+                    let light_pdf = scene.lights.pdf(&ray, hit.p());
+                    emitted = emitted * power_heuristic(pdf, light_pdf);
+                }
+            }
+            col = col + throughput * emitted;
+            if depth >= self.max_depth {
+                break;
+            }
+
+            let srec = match hit.scatter(&ray) {
+                Some(srec) => srec,
+                None => break,
+            };
+
+            // lights can only be sampled for materials with a known density
+            let sampled = !srec.specular && !scene.lights.is_empty();
+            if sampled {
+                col = col + throughput * sample_light(scene, &hit, &ray, &media);
+            }
+            if let Some(medium) = mat.medium() {
+                if (srec.ray.direction().dot(hit.ng()) < 0.0) == entering {
+                    media.cross(medium, entering);
+                }
+            }
+            throughput = throughput * srec.attenuation;
+
+            if depth >= self.rr_depth {
+                let p = throughput.0.max(throughput.1).max(throughput.2).min(0.95);
+                if uniform_in_range(0.0, 1.0) >= p {
+                    break;
+                }
+                throughput = throughput / p;
+            }
+
+            bsdf_pdf = if sampled { Some(srec.pdf) } else { None };
+            ray = srec.ray;
+            t_start = 0.0;
+            depth += 1;
+        }
+
+        col
+    }
+}
+
+/// Returns the light scattered at the hit along the ray that arrives directly
+/// from one light source, chosen by the light hierarchy of the scene.
+fn sample_light(scene: &Scene, hit: &Hit, r: &Ray, media: &MediumStack) -> Vec3 {
+    let (light, pmf) = match scene.lights.sample(hit.p(), uniform_in_range(0.0, 1.0)) {
+        Some(choice) => choice,
+        None => return Vec3(0.0, 0.0, 0.0),
+    };
+    let ls = match light.sample(hit.p(), r.time()) {
+        Some(ls) => ls,
+        None => return Vec3(0.0, 0.0, 0.0),
+    };
+
+    let wo = -r.direction().as_unit();
+    let f = hit.mat_ref().eval(hit, ls.dir, wo);
+    if f == Vec3(0.0, 0.0, 0.0) {
+        return Vec3(0.0, 0.0, 0.0);
+    }
+    let shadow_ray = Ray::new(hit.p(), ls.dir, r.time());
+    if scene
+        .world
+        .hit(&shadow_ray, 0.001, ls.dist - 0.001)
+        .is_some()
+    {
+        return Vec3(0.0, 0.0, 0.0);
+    }
+
+    // the light is absorbed by the medium on its side of the surface
+    let wo_side = wo.dot(hit.ng());
+    let transmittance = match hit.mat_ref().medium() {
+        Some(medium) if ls.dir.dot(hit.ng()) * wo_side < 0.0 => {
+            let mut beyond = media.clone();
+            beyond.cross(medium, wo_side > 0.0);
+            beyond.transmittance(ls.dist)
+        }
+        _ => media.transmittance(ls.dist),
+    };
+    let f = f * transmittance;
+
+    // delta lights can not be hit by scattered rays, so their samples are
+    // only weighted by the probability of choosing the light
+    let pdf = pmf * ls.pdf;
+    if light.is_delta() {
+        return f * ls.radiance / pdf;
+    }
+
+    // the direction could also have been sampled by the material
+    let weight = power_heuristic(pdf, hit.mat_ref().pdf(hit, ls.dir, wo));
+    f * ls.radiance * (weight / pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::background::ConstBackground;
+    use crate::mtl::Dielectric;
+    use crate::obj::{RectBox, XYRect};
+    use crate::tex::ConstTexture;
+    use crate::trace::{Camera, Hittable};
+
+    fn scene<H: Hittable + 'static>(world: H) -> Scene {
+        let camera = Camera::new(
+            Vec3(0.0, 0.0, 5.0),
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            90.0,
+            1.0,
+            0.0,
+            1.0,
+            0.0,
+            1.0,
+        );
+        let background = ConstBackground::new(Vec3(1.0, 1.0, 1.0));
+        Scene::new(camera, Arc::new(world), Vec::new(), Arc::new(background))
+    }
+
+    #[test]
+    fn test_absorbing_slab() {
+        // glass with the index of the air passes light straight through and
+        // only absorbs it, over the thickness of the slab
+        let absorption = Vec3(0.5, 0.2, 0.0);
+        let glass =
+            Dielectric::with_absorption(1.0, ConstTexture::new(Vec3(1.0, 1.0, 1.0)), absorption);
+        let thickness = 1.5;
+        let slab = RectBox::new(
+            Vec3(-1.0, -1.0, 0.0),
+            Vec3(1.0, 1.0, thickness),
+            Arc::new(glass),
+        );
+        let scene = scene(slab);
+
+        let integrator = PathIntegrator::new(50, 50);
+        let r = Ray::new(Vec3(0.0, 0.0, 5.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let expected = Vec3(
+            (-absorption.0 * thickness).exp(),
+            (-absorption.1 * thickness).exp(),
+            1.0,
+        );
+        for _ in 0..10 {
+            assert!((integrator.li(&r, &scene) - expected).len() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_escape_from_medium() {
+        // a single face that is entered but never left, so the ray escapes
+        // from inside the medium and its absorbed channels vanish
+        let absorption = Vec3(0.5, 0.0, 0.0);
+        let glass =
+            Dielectric::with_absorption(1.0, ConstTexture::new(Vec3(1.0, 1.0, 1.0)), absorption);
+        let scene = scene(XYRect::new(-1.0, -1.0, 1.0, 1.0, 0.0, glass));
+
+        let integrator = PathIntegrator::new(50, 50);
+        let r = Ray::new(Vec3(0.0, 0.0, 5.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert_eq!(integrator.li(&r, &scene), Vec3(0.0, 1.0, 1.0));
+    }
+}
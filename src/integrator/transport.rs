@@ -0,0 +1,12 @@
+use crate::math::Vec3;
+use crate::scene::Scene;
+use crate::trace::Ray;
+
+/// Integrator is a trait for light transport algorithms. It computes the color
+/// that a camera ray brings back from the scene. Integrators are shared between
+/// the render threads, so they must be Send and Sync.
+pub trait Integrator: Send + Sync {
+    /// Returns the light arriving at the origin of the ray from its direction.
+    ///     scene - scene with the objects, light sources and background
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3;
+}
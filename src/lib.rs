@@ -1,6 +1,11 @@
+pub mod background;
+pub mod integrator;
+pub mod light;
 pub mod math;
 pub mod mtl;
 pub mod obj;
+pub mod render;
+pub mod scene;
 pub mod strategy;
 pub mod tex;
 pub mod trace;
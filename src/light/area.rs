@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use crate::light::{Light, LightSample};
+use crate::math::Vec3;
+use crate::obj::AABB;
+use crate::trace::{Hittable, Ray};
+
+/// AreaLight is a light source made of an emissive object, e.g. a rectangle
+/// with a LightDiffuse material. Directions towards it are chosen with the
+/// random and pdf_value methods of the object.
+pub struct AreaLight {
+    shape: Arc<dyn Hittable>,
+}
+
+impl AreaLight {
+    /// Returns a new area light for the given emissive object.
+    pub fn new(shape: Arc<dyn Hittable>) -> AreaLight {
+        AreaLight { shape }
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(&self, p: Vec3, time: f32) -> Option<LightSample> {
+        let dir = self.shape.random(p, time);
+        let r = Ray::new(p, dir, time);
+        let pdf = self.shape.pdf_value(&r);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        let hit = self.shape.hit(&r, 0.001, f32::MAX)?;
+        Some(LightSample {
+            dir: dir.as_unit(),
+            dist: hit.t() * dir.len(),
+            radiance: hit.mat_ref().emitted(&r, &hit),
+            pdf,
+        })
+    }
+
+    fn pdf(&self, r: &Ray) -> f32 {
+        self.shape.pdf_value(r)
+    }
+
+    fn power(&self) -> f32 {
+        self.shape.power()
+    }
+
+    fn bounds(&self) -> Option<AABB> {
+        Some(self.shape.bounding_box(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::LightDiffuse;
+    use crate::obj::XZRect;
+    use crate::tex::ConstTexture;
+
+    #[test]
+    fn test_area_light_sample() {
+        // a 2x2 light, 2 units above the origin
+        let emit = LightDiffuse::new(ConstTexture::new(Vec3(4.0, 4.0, 4.0)), 1.0, true);
+        let light = AreaLight::new(Arc::new(XZRect::new(-1.0, -1.0, 1.0, 1.0, 2.0, emit)));
+
+        let mut mean = 0.0;
+        for _ in 0..2000 {
+            let ls = light.sample(Vec3(0.0, 0.0, 0.0), 0.0).unwrap();
+            assert!((ls.dir.len() - 1.0).abs() < 1e-4);
+            assert!(ls.dist >= 2.0 && ls.dist <= 6.0f32.sqrt() + 1e-4);
+            assert_eq!(ls.radiance, Vec3(4.0, 4.0, 4.0));
+
+            let r = Ray::new(Vec3(0.0, 0.0, 0.0), ls.dir, 0.0);
+            assert!((light.pdf(&r) - ls.pdf).abs() < 1e-3 * ls.pdf);
+            // estimate of the irradiance E, the integral of L * cos over the light
+            mean += ls.radiance.0 * ls.dir.1 / ls.pdf / 2000.0;
+        }
+
+        // the square is made of 4 unit squares with a corner above the origin,
+        // each giving E = L * a / sqrt(1 + a²) * atan(a / sqrt(1 + a²)) for a = 1/2
+        let a = 0.5f32;
+        let corner = a / (1.0 + a * a).sqrt() * (a / (1.0 + a * a).sqrt()).atan();
+        let expected = 4.0 * 4.0 * corner;
+        assert!((mean - expected).abs() < 0.05 * expected);
+    }
+}
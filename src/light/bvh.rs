@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use crate::light::Light;
+use crate::math::Vec3;
+use crate::obj::{surrounding_box, AABB};
+use crate::trace::Ray;
+
+// large enough for the deepest tree, as nodes are split at the median
+const STACK_SIZE: usize = 64;
+// keeps the importance of a node finite for points at its center
+const MIN_SQ_DIST: f32 = 1e-6;
+// largest f32 below 1, to keep the remapped random numbers in [0, 1)
+const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// LightBVH holds the light sources of a scene and chooses one of them for a point
+/// of the scene. Lights with bounds are kept in a bounding volume hierarchy, which is
+/// walked down from the root by picking each child with a probability proportional to
+/// its power divided by its squared distance to the point. Bright and near lights get
+/// most of the samples that way, also in scenes with many lights. Lights that are
+/// infinitely far away are chosen uniformly.
+/// The tree is stored flattened in depth-first order, like BVHNode.
+pub struct LightBVH {
+    lights: Vec<Arc<dyn Light>>,
+    infinite: Vec<usize>,
+    nodes: Vec<LightNode>,
+    leaves: Vec<Option<u32>>,
+}
+
+/// LightNode is a node of the flattened tree.
+///     power - sum of the power of the lights below the node
+///     offset - index of the light for leaves, index of the second child otherwise
+///     parent - index of the parent node, 0 for the root
+struct LightNode {
+    min: Vec3,
+    max: Vec3,
+    power: f32,
+    offset: u32,
+    parent: u32,
+    leaf: bool,
+}
+
+/// Entry is a light with bounds, with its precomputed centroid and power.
+struct Entry {
+    idx: usize,
+    bbox: AABB,
+    centroid: Vec3,
+    power: f32,
+}
+
+impl LightBVH {
+    /// Returns a new hierarchy for the lights. The lights keep their order.
+    pub fn new(lights: Vec<Arc<dyn Light>>) -> LightBVH {
+        let mut infinite = Vec::new();
+        let mut entries = Vec::new();
+        for (idx, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bbox) => entries.push(Entry {
+                    idx,
+                    centroid: (bbox.min() + bbox.max()) / 2.0,
+                    bbox,
+                    power: light.power().max(0.0),
+                }),
+                None => infinite.push(idx),
+            }
+        }
+
+        let mut bvh = LightBVH {
+            leaves: vec![None; lights.len()],
+            lights,
+            infinite,
+            nodes: Vec::with_capacity(2 * entries.len()),
+        };
+        if !entries.is_empty() {
+            bvh.build(entries, 0);
+        }
+        bvh
+    }
+
+    /// Returns the number of lights.
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Returns whether there are no lights.
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Returns the light with the given index.
+    pub fn get(&self, idx: usize) -> Option<&dyn Light> {
+        self.lights.get(idx).map(|light| light.as_ref())
+    }
+
+    /// Returns an iterator over the lights.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Light> {
+        self.lights.iter().map(|light| light.as_ref())
+    }
+
+    /// Chooses a light for the point p with the uniform random number u. Returns
+    /// the light together with the probability of choosing it, or None if no
+    /// light has any power.
+    pub fn sample(&self, p: Vec3, u: f32) -> Option<(&dyn Light, f32)> {
+        let p_infinite = self.infinite_probability();
+        if u < p_infinite {
+            let n = self.infinite.len();
+            let i = ((u / p_infinite * n as f32) as usize).min(n - 1);
+            return Some((
+                self.lights[self.infinite[i]].as_ref(),
+                p_infinite / n as f32,
+            ));
+        }
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut u = ((u - p_infinite) / (1.0 - p_infinite)).min(ONE_MINUS_EPSILON);
+        let mut pmf = 1.0 - p_infinite;
+        let mut idx = 0;
+        while !self.nodes[idx].leaf {
+            let (first, second) = (idx + 1, self.nodes[idx].offset as usize);
+            let p_first = self.split(p, first, second)?;
+            if u < p_first {
+                u /= p_first;
+                pmf *= p_first;
+                idx = first;
+            } else {
+                u = (u - p_first) / (1.0 - p_first);
+                pmf *= 1.0 - p_first;
+                idx = second;
+            }
+            u = u.min(ONE_MINUS_EPSILON);
+        }
+        let light = self.nodes[idx].offset as usize;
+        Some((self.lights[light].as_ref(), pmf))
+    }
+
+    /// Returns the probability with which sample chooses the light with the given
+    /// index for the point p.
+    pub fn pmf(&self, p: Vec3, light: usize) -> f32 {
+        let p_infinite = self.infinite_probability();
+        let mut idx = match self.leaves[light] {
+            Some(leaf) => leaf as usize,
+            None => return p_infinite / self.infinite.len() as f32,
+        };
+
+        // the choices on the way up to the root
+        let mut pmf = 1.0 - p_infinite;
+        while idx != 0 {
+            let parent = self.nodes[idx].parent as usize;
+            let (first, second) = (parent + 1, self.nodes[parent].offset as usize);
+            let p_first = match self.split(p, first, second) {
+                Some(p_first) => p_first,
+                None => return 0.0,
+            };
+            pmf *= if idx == first { p_first } else { 1.0 - p_first };
+            idx = parent;
+        }
+        pmf
+    }
+
+    /// Returns the density with which a light is chosen and sampled in the
+    /// direction of the ray, where the ray hits the surface of a light at p.
+    pub fn pdf(&self, r: &Ray, p: Vec3) -> f32 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+
+        // only the lights whose bounds contain p can have been hit
+        let eps = 1e-4 * (1.0 + p.0.abs().max(p.1.abs()).max(p.2.abs()));
+        let mut pdf = 0.0;
+        let mut stack = [0usize; STACK_SIZE];
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let node = &self.nodes[stack[top]];
+            if !node.contains(p, eps) {
+                continue;
+            }
+            if node.leaf {
+                let light = node.offset as usize;
+                pdf += self.pmf(r.origin(), light) * self.lights[light].pdf(r);
+            } else {
+                stack[top] += 1;
+                stack[top + 1] = node.offset as usize;
+                top += 2;
+            }
+        }
+        pdf
+    }
+
+    /// Returns the density with which a light infinitely far away is chosen and
+    /// sampled in the direction of the ray.
+    pub fn infinite_pdf(&self, r: &Ray) -> f32 {
+        let pmf = self.infinite_probability() / self.infinite.len().max(1) as f32;
+        self.infinite
+            .iter()
+            .map(|&idx| pmf * self.lights[idx].pdf(r))
+            .sum()
+    }
+
+    /// Returns the probability of choosing one of the lights infinitely far
+    /// away, which counts like a single light against the tree.
+    fn infinite_probability(&self) -> f32 {
+        if self.infinite.is_empty() {
+            0.0
+        } else if self.nodes.is_empty() {
+            1.0
+        } else {
+            let n = self.infinite.len() as f32;
+            n / (n + 1.0)
+        }
+    }
+
+    /// Returns the probability of choosing the first of two children for the
+    /// point p, or None if neither child has any importance.
+    fn split(&self, p: Vec3, first: usize, second: usize) -> Option<f32> {
+        let i_first = self.nodes[first].importance(p);
+        let i_second = self.nodes[second].importance(p);
+        if i_first + i_second <= 0.0 {
+            return None;
+        }
+        Some(i_first / (i_first + i_second))
+    }
+
+    /// Appends the subtree for the given entries and returns the index of its root.
+    fn build(&mut self, mut entries: Vec<Entry>, parent: usize) -> usize {
+        let bbox = entries.iter().fold(AABB::new_hidden(), |res, entry| {
+            surrounding_box(res, entry.bbox.clone())
+        });
+        let power = entries.iter().map(|entry| entry.power).sum();
+        let idx = self.nodes.len();
+        self.nodes.push(LightNode {
+            min: bbox.min(),
+            max: bbox.max(),
+            power,
+            offset: 0,
+            parent: parent as u32,
+            leaf: entries.len() == 1,
+        });
+        if entries.len() == 1 {
+            self.nodes[idx].offset = entries[0].idx as u32;
+            self.leaves[entries[0].idx] = Some(idx as u32);
+            return idx;
+        }
+
+        // split at the median centroid along the longest axis
+        let centroid_bbox = entries.iter().fold(AABB::new_hidden(), |res, entry| {
+            surrounding_box(res, AABB::new(entry.centroid, entry.centroid))
+        });
+        let extent = centroid_bbox.max() - centroid_bbox.min();
+        let axis = if extent.0 >= extent.1 && extent.0 >= extent.2 {
+            0
+        } else if extent.1 >= extent.2 {
+            1
+        } else {
+            2
+        };
+        entries.sort_by(|e1, e2| axis_of(e1.centroid, axis).total_cmp(&axis_of(e2.centroid, axis)));
+        let right = entries.split_off(entries.len() / 2);
+
+        self.build(entries, idx);
+        self.nodes[idx].offset = self.build(right, idx) as u32;
+        idx
+    }
+}
+
+impl LightNode {
+    /// Returns how much light the node is expected to send to the point p.
+    fn importance(&self, p: Vec3) -> f32 {
+        let center = (self.min + self.max) / 2.0;
+        let sq_radius = (self.max - self.min).sq_len() / 4.0;
+        let sq_dist = (p - center).sq_len().max(sq_radius).max(MIN_SQ_DIST);
+        self.power / sq_dist
+    }
+
+    /// Returns whether p is inside the node's box, grown by eps.
+    fn contains(&self, p: Vec3, eps: f32) -> bool {
+        p.0 >= self.min.0 - eps
+            && p.0 <= self.max.0 + eps
+            && p.1 >= self.min.1 - eps
+            && p.1 <= self.max.1 + eps
+            && p.2 >= self.min.2 - eps
+            && p.2 <= self.max.2 + eps
+    }
+}
+
+#[inline]
+fn axis_of(v: Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.0,
+        1 => v.1,
+        _ => v.2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::{DirectionalLight, PointLight};
+
+    fn point_lights(lights: &[(Vec3, f32)]) -> Vec<Arc<dyn Light>> {
+        lights
+            .iter()
+            .map(|&(p, i)| Arc::new(PointLight::new(p, Vec3(i, i, i))) as Arc<dyn Light>)
+            .collect()
+    }
+
+    #[test]
+    fn test_light_bvh_pmf() {
+        let bvh = LightBVH::new(point_lights(&[
+            (Vec3(0.0, 0.0, 0.0), 1.0),
+            (Vec3(10.0, 0.0, 0.0), 1.0),
+            (Vec3(20.0, 0.0, 0.0), 4.0),
+            (Vec3(30.0, 0.0, 0.0), 0.0),
+        ]));
+        assert_eq!(bvh.len(), 4);
+
+        // the probabilities sum to 1 and the light next to the point is preferred
+        let p = Vec3(1.0, 1.0, 0.0);
+        let pmfs: Vec<f32> = (0..4).map(|i| bvh.pmf(p, i)).collect();
+        assert!((pmfs.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(pmfs[0] > 0.8);
+        assert_eq!(pmfs[3], 0.0);
+
+        // sample agrees with pmf
+        let n = 1000;
+        for i in 0..n {
+            let u = (i as f32 + 0.5) / n as f32;
+            let (light, pmf) = bvh.sample(p, u).unwrap();
+            let idx = (light.bounds().unwrap().min().0 / 10.0) as usize;
+            assert!((pmf - pmfs[idx]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_light_bvh_infinite() {
+        let mut lights = point_lights(&[(Vec3(0.0, 0.0, 0.0), 1.0)]);
+        lights.push(Arc::new(DirectionalLight::new(
+            Vec3(0.0, -1.0, 0.0),
+            Vec3(1.0, 1.0, 1.0),
+        )));
+        let bvh = LightBVH::new(lights);
+
+        let p = Vec3(0.0, 5.0, 0.0);
+        assert_eq!(bvh.pmf(p, 0), 0.5);
+        assert_eq!(bvh.pmf(p, 1), 0.5);
+        assert!(bvh.sample(p, 0.25).unwrap().0.bounds().is_none());
+        assert!(bvh.sample(p, 0.75).unwrap().0.bounds().is_some());
+
+        assert!(LightBVH::new(Vec::new()).sample(p, 0.5).is_none());
+    }
+}
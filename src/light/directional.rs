@@ -0,0 +1,41 @@
+use crate::light::{Light, LightSample};
+use crate::math::Vec3;
+use crate::trace::Ray;
+
+/// DirectionalLight is a light source infinitely far away, e.g. the sun, whose
+/// light arrives from the same direction everywhere in the scene.
+///     direction - direction in which the light travels
+///     irradiance - light arriving on a surface facing the light
+pub struct DirectionalLight {
+    direction: Vec3,
+    irradiance: Vec3,
+}
+
+impl DirectionalLight {
+    /// Returns a new directional light.
+    pub fn new(direction: Vec3, irradiance: Vec3) -> DirectionalLight {
+        DirectionalLight {
+            direction: direction.as_unit(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _: Vec3, _: f32) -> Option<LightSample> {
+        Some(LightSample {
+            dir: -self.direction,
+            dist: f32::MAX,
+            radiance: self.irradiance,
+            pdf: 1.0,
+        })
+    }
+
+    fn pdf(&self, _: &Ray) -> f32 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
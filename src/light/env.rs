@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use crate::background::Background;
+use crate::light::{Light, LightSample};
+use crate::math::Vec3;
+use crate::trace::Ray;
+
+/// EnvLight is a light source made of a background that can be sampled, e.g.
+/// an environment map. It lights the scene from infinitely far away.
+pub struct EnvLight {
+    background: Arc<dyn Background>,
+}
+
+impl EnvLight {
+    /// Returns a new light for the given background.
+    pub fn new(background: Arc<dyn Background>) -> EnvLight {
+        EnvLight { background }
+    }
+}
+
+impl Light for EnvLight {
+    fn sample(&self, _: Vec3, _: f32) -> Option<LightSample> {
+        let dir = self.background.sample();
+        let pdf = self.background.pdf(dir);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            dir,
+            dist: f32::MAX,
+            radiance: self.background.radiance(dir),
+            pdf,
+        })
+    }
+
+    fn pdf(&self, r: &Ray) -> f32 {
+        self.background.pdf(r.direction().as_unit())
+    }
+}
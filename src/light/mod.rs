@@ -0,0 +1,15 @@
+pub use area::AreaLight;
+pub use bvh::LightBVH;
+pub use directional::DirectionalLight;
+pub use env::EnvLight;
+pub use point::PointLight;
+pub use source::{Light, LightSample};
+pub use spot::SpotLight;
+
+mod area;
+mod bvh;
+mod directional;
+mod env;
+mod point;
+mod source;
+mod spot;
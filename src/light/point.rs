@@ -0,0 +1,78 @@
+use std::f32::consts::PI;
+
+use crate::light::{Light, LightSample};
+use crate::math::Vec3;
+use crate::obj::AABB;
+use crate::trace::Ray;
+
+/// PointLight is a light source at a single point that shines equally in all
+/// directions. It can not be seen by the camera, only its light on the scene.
+///     position - location of the light
+///     intensity - power per solid angle, which falls off with the square of
+///                 the distance
+pub struct PointLight {
+    position: Vec3,
+    intensity: Vec3,
+}
+
+impl PointLight {
+    /// Returns a new point light.
+    pub fn new(position: Vec3, intensity: Vec3) -> PointLight {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: Vec3, _: f32) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let sq_dist = to_light.sq_len();
+        if sq_dist <= 0.0 {
+            return None;
+        }
+
+        let dist = sq_dist.sqrt();
+        Some(LightSample {
+            dir: to_light / dist,
+            dist,
+            radiance: self.intensity / sq_dist,
+            pdf: 1.0,
+        })
+    }
+
+    fn pdf(&self, _: &Ray) -> f32 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        4.0 * PI * self.intensity.luminance()
+    }
+
+    fn bounds(&self) -> Option<AABB> {
+        Some(AABB::new(self.position, self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_light_sample() {
+        let light = PointLight::new(Vec3(0.0, 2.0, 0.0), Vec3(8.0, 4.0, 4.0));
+        let ls = light.sample(Vec3(0.0, 0.0, 0.0), 0.0).unwrap();
+        assert_eq!(ls.dir, Vec3(0.0, 1.0, 0.0));
+        assert_eq!(ls.dist, 2.0);
+        assert_eq!(ls.radiance, Vec3(2.0, 1.0, 1.0));
+        assert!(light.is_delta());
+
+        let r = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0), 0.0);
+        assert_eq!(light.pdf(&r), 0.0);
+    }
+}
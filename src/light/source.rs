@@ -0,0 +1,49 @@
+use crate::math::Vec3;
+use crate::obj::AABB;
+use crate::trace::Ray;
+
+/// LightSample is a direction towards a light source, chosen by Light::sample:
+///     dir - unit vector from the sampled point towards the light
+///     dist - distance to the light along dir
+///     radiance - light arriving at the sampled point from dir
+///     pdf - density of choosing dir, with respect to solid angle, or 1 for
+///           delta lights
+pub struct LightSample {
+    pub dir: Vec3,
+    pub dist: f32,
+    pub radiance: Vec3,
+    pub pdf: f32,
+}
+
+/// Light is a trait for light sources that can be sampled directly, i.e. for
+/// which a direction towards the light can be chosen from any point of the
+/// scene. Lights are shared between the render threads, so they must be Send
+/// and Sync.
+pub trait Light: Send + Sync {
+    /// Returns a random direction from the point p towards the light, or None
+    /// if the light can not be reached from p. The time is that of the ray
+    /// that hit p.
+    fn sample(&self, p: Vec3, time: f32) -> Option<LightSample>;
+
+    /// Returns the density with which sample chooses the direction of the ray
+    /// from its origin.
+    fn pdf(&self, r: &Ray) -> f32;
+
+    /// Returns true for lights that are a single point or direction, which
+    /// rays can not hit and which are only found by sampling them.
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// Returns an estimate of the power of the light, by which lights that
+    /// have bounds are chosen.
+    fn power(&self) -> f32 {
+        0.0
+    }
+
+    /// Returns the box that contains the light, or None for lights that are
+    /// infinitely far away.
+    fn bounds(&self) -> Option<AABB> {
+        None
+    }
+}
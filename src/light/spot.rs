@@ -0,0 +1,127 @@
+use std::f32::consts::PI;
+
+use crate::light::{Light, LightSample};
+use crate::math::Vec3;
+use crate::obj::AABB;
+use crate::trace::Ray;
+
+/// SpotLight is a point light that only shines into a cone. Towards the edge of
+/// the cone the light fades out smoothly.
+///     position - location of the light
+///     direction - axis of the cone
+///     intensity - power per solid angle at the axis of the cone
+///     angle - angle in degrees between the axis and the edge of the cone
+///     falloff - width in degrees of the soft edge inside the cone
+pub struct SpotLight {
+    position: Vec3,
+    direction: Vec3,
+    intensity: Vec3,
+    cos_total: f32,
+    cos_falloff: f32,
+}
+
+impl SpotLight {
+    /// Returns a new spot light.
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        intensity: Vec3,
+        angle: f32,
+        falloff: f32,
+    ) -> SpotLight {
+        let angle = angle.clamp(0.0, 180.0);
+        let falloff = falloff.clamp(0.0, angle);
+        SpotLight {
+            position,
+            direction: direction.as_unit(),
+            intensity,
+            cos_total: angle.to_radians().cos(),
+            cos_falloff: (angle - falloff).to_radians().cos(),
+        }
+    }
+
+    /// Returns the fraction of the intensity that is sent into the direction
+    /// with the given cosine to the axis.
+    fn falloff(&self, cos_theta: f32) -> f32 {
+        if cos_theta < self.cos_total {
+            return 0.0;
+        }
+        if cos_theta >= self.cos_falloff {
+            return 1.0;
+        }
+        // smoothstep between the edge and the start of the falloff
+        let t = (cos_theta - self.cos_total) / (self.cos_falloff - self.cos_total);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: Vec3, _: f32) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let sq_dist = to_light.sq_len();
+        if sq_dist <= 0.0 {
+            return None;
+        }
+
+        let dist = sq_dist.sqrt();
+        let dir = to_light / dist;
+        let falloff = self.falloff(-dir.dot(self.direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            dir,
+            dist,
+            radiance: self.intensity * (falloff / sq_dist),
+            pdf: 1.0,
+        })
+    }
+
+    fn pdf(&self, _: &Ray) -> f32 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        // the cone, counting the soft edge half
+        let cos_mid = (self.cos_total + self.cos_falloff) / 2.0;
+        2.0 * PI * (1.0 - cos_mid) * self.intensity.luminance()
+    }
+
+    fn bounds(&self) -> Option<AABB> {
+        Some(AABB::new(self.position, self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spot_light_cone() {
+        // pointing down from 1 unit above the origin, fading out from 30 to 45°
+        let light = SpotLight::new(
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, -1.0, 0.0),
+            Vec3(1.0, 1.0, 1.0),
+            45.0,
+            15.0,
+        );
+
+        let ls = light.sample(Vec3(0.0, 0.0, 0.0), 0.0).unwrap();
+        assert_eq!(ls.radiance, Vec3(1.0, 1.0, 1.0));
+        assert_eq!(ls.dir, Vec3(0.0, 1.0, 0.0));
+
+        // 40° off the axis is in the soft edge
+        let x = 40.0f32.to_radians().tan();
+        let ls = light.sample(Vec3(x, 0.0, 0.0), 0.0).unwrap();
+        let unfaded = 1.0 / (1.0 + x * x);
+        assert!(ls.radiance.0 > 0.0 && ls.radiance.0 < unfaded);
+
+        assert!(light.sample(Vec3(2.0, 0.0, 0.0), 0.0).is_none());
+        assert!(light.sample(Vec3(0.0, 2.0, 0.0), 0.0).is_none());
+    }
+}
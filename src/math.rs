@@ -44,6 +44,12 @@ impl Vec3 {
     pub fn as_unit(self) -> Vec3 {
         self / self.len()
     }
+
+    /// Returns the luminance of the vector as a linear sRGB color.
+    #[inline]
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
 }
 
 impl std::ops::Add<Vec3> for Vec3 {
@@ -132,6 +138,58 @@ impl std::fmt::Display for Vec3 {
     }
 }
 
+/// ONB is an orthonormal basis (u, v, w). It is used to express directions
+/// that are sampled around w, e.g. around a surface normal, in world space.
+#[derive(Copy, Clone, Debug)]
+pub struct ONB {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl ONB {
+    /// Returns a basis whose w axis points in the direction of the given vector.
+    pub fn from_w(w: Vec3) -> ONB {
+        let w = w.as_unit();
+        let a = if w.0.abs() > 0.9 {
+            Vec3(0.0, 1.0, 0.0)
+        } else {
+            Vec3(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(a).as_unit();
+        let u = w.cross(v);
+        ONB { u, v, w }
+    }
+
+    /// Accessor for onb.u.
+    pub fn u(&self) -> Vec3 {
+        self.u
+    }
+
+    /// Accessor for onb.v.
+    pub fn v(&self) -> Vec3 {
+        self.v
+    }
+
+    /// Accessor for onb.w.
+    pub fn w(&self) -> Vec3 {
+        self.w
+    }
+
+    /// Returns the world space vector for the coordinates a in the basis.
+    #[inline]
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.0 * self.u + a.1 * self.v + a.2 * self.w
+    }
+
+    /// Returns the coordinates in the basis for the world space vector a, the
+    /// inverse of local.
+    #[inline]
+    pub fn coords(&self, a: Vec3) -> Vec3 {
+        Vec3(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +217,15 @@ mod tests {
         assert_eq!(v1.cross(v2), Vec3(0.0, 0.0, 0.0));
         assert_eq!(v1.dot(v2), 6.0);
     }
+
+    #[test]
+    fn test_onb() {
+        let onb = ONB::from_w(Vec3(0.0, 3.0, 4.0));
+        assert_eq!(onb.w(), Vec3(0.0, 0.6, 0.8));
+        assert!(onb.u().dot(onb.v()).abs() < 1e-6);
+        assert!(onb.u().dot(onb.w()).abs() < 1e-6);
+        assert!(onb.v().dot(onb.w()).abs() < 1e-6);
+        assert!((onb.u().len() - 1.0).abs() < 1e-6);
+        assert!((onb.local(Vec3(0.0, 0.0, 2.0)) - Vec3(0.0, 1.2, 1.6)).len() < 1e-6);
+    }
 }
@@ -0,0 +1,179 @@
+use crate::math::{Vec3, ONB};
+use crate::trace::{Hit, Ray};
+use crate::utils::rng::uniform_in_range;
+
+use super::microfacet::GGX;
+use super::utils as mtl_utils;
+use super::{ScatterRecord, Scatterable};
+
+/// Conductor is a metal with microfacets distributed by GGX, whose color comes
+/// from the Fresnel reflectance of its complex index of refraction:
+///     eta - real part of the index of refraction for red, green and blue
+///     k - imaginary part, the absorption coefficient, per channel
+///     ggx - distribution of the microfacets
+pub struct Conductor {
+    eta: Vec3,
+    k: Vec3,
+    ggx: GGX,
+}
+
+impl Conductor {
+    /// Returns a conductor with the perceptual roughness in [0, 1].
+    pub fn new(eta: Vec3, k: Vec3, roughness: f32) -> Conductor {
+        Conductor {
+            eta,
+            k,
+            ggx: GGX::new(roughness),
+        }
+    }
+
+    /// Returns the complex index of refraction (eta, k) of a named metal, one
+    /// of gold, silver, copper or aluminium.
+    pub fn preset(name: &str) -> Option<(Vec3, Vec3)> {
+        match name {
+            "gold" => Some((Vec3(0.143, 0.374, 1.442), Vec3(3.983, 2.386, 1.603))),
+            "silver" => Some((Vec3(0.155, 0.117, 0.138), Vec3(4.828, 3.122, 2.147))),
+            "copper" => Some((Vec3(0.200, 0.924, 1.102), Vec3(3.912, 2.452, 2.142))),
+            "aluminium" => Some((Vec3(1.657, 0.880, 0.521), Vec3(9.224, 6.270, 4.837))),
+            _ => None,
+        }
+    }
+
+    fn fresnel(&self, cos_i: f32) -> Vec3 {
+        Vec3(
+            mtl_utils::fresnel_conductor(cos_i, self.eta.0, self.k.0),
+            mtl_utils::fresnel_conductor(cos_i, self.eta.1, self.k.1),
+            mtl_utils::fresnel_conductor(cos_i, self.eta.2, self.k.2),
+        )
+    }
+
+    /// Returns the frame of the surface, with the normal on the side of wo.
+    fn frame(hit: &Hit, wo: Vec3) -> ONB {
+        if wo.dot(hit.ng()) >= 0.0 {
+            ONB::from_w(hit.n())
+        } else {
+            ONB::from_w(-hit.n())
+        }
+    }
+}
+
+impl Scatterable for Conductor {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        let onb = Self::frame(hit, -r.direction().as_unit());
+        let wo = onb.coords(-r.direction().as_unit());
+        if wo.2 <= 0.0 {
+            return None;
+        }
+
+        if self.ggx.is_smooth() {
+            let wi = Vec3(-wo.0, -wo.1, wo.2);
+            let scattered = Ray::new(hit.p(), onb.local(wi), r.time());
+            return Some(ScatterRecord::specular(scattered, self.fresnel(wo.2)));
+        }
+
+        let h = self
+            .ggx
+            .sample_visible(wo, uniform_in_range(0.0, 1.0), uniform_in_range(0.0, 1.0));
+        let cos_h = wo.dot(h);
+        let wi = 2.0 * cos_h * h - wo;
+        if wi.2 <= 0.0 || cos_h <= 0.0 {
+            return None;
+        }
+
+        // eval / pdf, D and the cosines cancel out
+        Some(ScatterRecord {
+            ray: Ray::new(hit.p(), onb.local(wi), r.time()),
+            attenuation: self.fresnel(cos_h) * (self.ggx.g(wo, wi) / self.ggx.g1(wo)),
+            pdf: self.ggx.pdf_visible(wo, h) / (4.0 * cos_h),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> Vec3 {
+        let onb = Self::frame(hit, wo);
+        let (wi, wo) = (onb.coords(wi), onb.coords(wo));
+        if self.ggx.is_smooth() || wi.2 <= 0.0 || wo.2 <= 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+        let h = (wi + wo).as_unit();
+        let cos_h = wo.dot(h).max(0.0);
+        self.fresnel(cos_h) * (self.ggx.d(h) * self.ggx.g(wo, wi) / (4.0 * wo.2))
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> f32 {
+        let onb = Self::frame(hit, wo);
+        let (wi, wo) = (onb.coords(wi), onb.coords(wo));
+        if self.ggx.is_smooth() || wi.2 <= 0.0 || wo.2 <= 0.0 {
+            return 0.0;
+        }
+        let h = (wi + wo).as_unit();
+        let cos_h = wo.dot(h);
+        if cos_h <= 0.0 {
+            return 0.0;
+        }
+        self.ggx.pdf_visible(wo, h) / (4.0 * cos_h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(mat: &Conductor) -> Hit<'_> {
+        Hit::new(1.0, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0), mat, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_conductor_mirror() {
+        let (eta, k) = Conductor::preset("gold").unwrap();
+        let mat = Conductor::new(eta, k, 0.0);
+        let r = Ray::new(Vec3(-1.0, 1.0, 0.0), Vec3(1.0, -1.0, 0.0), 0.0);
+
+        let rec = mat.scatter(&r, &hit(&mat)).unwrap();
+        assert!(rec.specular);
+        assert!((rec.ray.direction() - Vec3(1.0, 1.0, 0.0).as_unit()).len() < 1e-5);
+        // gold reflects red more than blue
+        assert!(rec.attenuation.0 > rec.attenuation.2);
+        assert!(Conductor::preset("unobtainium").is_none());
+    }
+
+    #[test]
+    fn test_conductor_rough() {
+        let (eta, k) = Conductor::preset("copper").unwrap();
+        let mat = Conductor::new(eta, k, 0.5);
+        let hit = hit(&mat);
+        let r = Ray::new(Vec3(-1.0, 2.0, 0.5), Vec3(1.0, -2.0, -0.5), 0.0);
+        let wo = -r.direction().as_unit();
+
+        for _ in 0..100 {
+            let rec = match mat.scatter(&r, &hit) {
+                Some(rec) => rec,
+                None => continue,
+            };
+            let wi = rec.ray.direction().as_unit();
+            assert!(!rec.specular);
+            assert!((rec.pdf - mat.pdf(&hit, wi, wo)).abs() < 1e-3 * rec.pdf);
+
+            let expected = mat.eval(&hit, wi, wo) / rec.pdf;
+            assert!((rec.attenuation - expected).len() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_conductor_energy() {
+        // a perfect conductor only loses energy to shadowing by microfacets
+        let mat = Conductor::new(Vec3(0.0, 0.0, 0.0), Vec3(1e4, 1e4, 1e4), 0.3);
+        let hit = hit(&mat);
+        let r = Ray::new(Vec3(0.0, 1.0, 0.0), Vec3(0.2, -1.0, 0.0), 0.0);
+
+        let n = 10_000;
+        let mut sum = 0.0;
+        for _ in 0..n {
+            if let Some(rec) = mat.scatter(&r, &hit) {
+                sum += rec.attenuation.1;
+            }
+        }
+        let albedo = sum / n as f32;
+        assert!(albedo > 0.9 && albedo <= 1.0);
+    }
+}
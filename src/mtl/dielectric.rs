@@ -4,46 +4,117 @@ use crate::tex::Wrappable;
 use crate::utils::rng::uniform_in_range;
 
 use super::utils as mtl_utils;
-use super::Scatterable;
+use super::{Medium, ScatterRecord, Scatterable};
 
+/// Dielectric is smooth glass that reflects or refracts rays:
+///     medium - medium inside the glass, i.e. opposite the normal, whose index
+///              of refraction is relative to hit.outside_ior
+///     albedo - tint of the light at every reflection and refraction
 pub struct Dielectric<W: Wrappable> {
-    rfn_ind: f32,
+    medium: Medium,
     albedo: W,
 }
 
 impl <W: Wrappable> Dielectric<W> {
+    /// Returns glass with the index of refraction rfn_ind that does not absorb
+    /// light inside.
     pub fn new(rfn_ind: f32, albedo: W) -> Dielectric<W> {
-        Dielectric { rfn_ind, albedo }
+        Dielectric::with_absorption(rfn_ind, albedo, Vec3(0.0, 0.0, 0.0))
+    }
+
+    /// Returns glass that absorbs light following the Beer-Lambert law, so
+    /// thick glass is more strongly colored than thin glass.
+    pub fn with_absorption(rfn_ind: f32, albedo: W, absorption: Vec3) -> Dielectric<W> {
+        Dielectric::with_medium(Medium::new(rfn_ind, absorption, 0), albedo)
+    }
+
+    /// Returns glass filled with the medium, which may absorb light and have
+    /// a priority over the media of overlapping objects.
+    pub fn with_medium(medium: Medium, albedo: W) -> Dielectric<W> {
+        Dielectric { medium, albedo }
     }
 }
 
 impl <W: Wrappable> Scatterable for Dielectric<W> {
-    fn scatter(&self, r: &Ray, hit: Hit) -> Option<(Ray, Vec3)> {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
         let reflected = mtl_utils::reflect(r.direction().as_unit(), hit.n());
+        let rfn_ind = self.medium.ior / hit.outside_ior();
 
-        let (out_norm, ni_nt, cos) = if r.direction().dot(hit.n()) > 0.0 {
+        let (out_norm, ni_nt, cos) = if r.direction().dot(hit.ng()) > 0.0 {
             (
                 -hit.n(),
-                self.rfn_ind,
-                self.rfn_ind * r.direction().dot(hit.n()) / r.direction().len(),
+                rfn_ind,
+                rfn_ind * r.direction().dot(hit.n()) / r.direction().len(),
             )
         } else {
             (
                 hit.n(),
-                1.0 / self.rfn_ind,
+                1.0 / rfn_ind,
                 -r.direction().dot(hit.n()) / r.direction().len(),
             )
         };
 
         let (refracted, reflect_prob) = match mtl_utils::refract(r.direction().as_unit(), out_norm, ni_nt) {
-            Some(refracted) => (Some(refracted), mtl_utils::schlick(self.rfn_ind, cos)),
+            Some(refracted) => (Some(refracted), mtl_utils::schlick(rfn_ind, cos)),
             None => (None, 1.0),
         };
 
+        let albedo = self.albedo.value(hit.u(), hit.v(), hit.p());
         if uniform_in_range(0.0, 1.0) < reflect_prob {
-            Some((Ray::new(hit.p(), reflected, r.time()), self.albedo.value(hit.u(), hit.v(), hit.p())))
+            Some(ScatterRecord::specular(Ray::new(hit.p(), reflected, r.time()), albedo))
         } else {
-            Some((Ray::new(hit.p(), refracted?, r.time()), self.albedo.value(hit.u(), hit.v(), hit.p())))
+            Some(ScatterRecord::specular(Ray::new(hit.p(), refracted?, r.time()), albedo))
         }
     }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(self.medium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tex::ConstTexture;
+
+    #[test]
+    fn test_dielectric_outside_ior() {
+        let white = ConstTexture::new(Vec3(1.0, 1.0, 1.0));
+        let mat = Dielectric::new(1.33, white);
+        let mut hit = Hit::new(1.0, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0), &mat, 0.0, 0.0);
+        let r = Ray::new(Vec3(-1.0, 1.0, 0.0), Vec3(1.0, -1.0, 0.0), 0.0);
+
+        // from air into water the ray bends towards the normal
+        let bent = (0..100)
+            .filter_map(|_| mat.scatter(&r, &hit))
+            .find(|rec| rec.ray.direction().1 < 0.0)
+            .unwrap();
+        assert!(bent.ray.direction().as_unit().1 < -0.75);
+
+        // between two media with the same index it goes straight on
+        hit.set_outside_ior(1.33);
+        for _ in 0..100 {
+            let rec = mat.scatter(&r, &hit).unwrap();
+            if rec.ray.direction().1 < 0.0 {
+                assert!((rec.ray.direction().as_unit() - r.direction().as_unit()).len() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dielectric_absorption() {
+        let white = ConstTexture::new(Vec3(1.0, 1.0, 1.0));
+        let mat = Dielectric::with_absorption(1.5, white, Vec3(0.5, 0.1, 0.0));
+        let medium = mat.medium().unwrap();
+        assert_eq!(medium.ior, 1.5);
+
+        // the path integrator absorbs the light inside, not the surface
+        let hit = Hit::new(2.0, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0), &mat, 0.0, 0.0);
+        let r = Ray::new(Vec3(0.0, -2.0, 0.0), Vec3(0.0, 1.0, 0.0), 0.0);
+        assert_eq!(mat.scatter(&r, &hit).unwrap().attenuation, Vec3(1.0, 1.0, 1.0));
+
+        // after a distance of 2 inside the glass
+        let expected = Vec3((-1.0f32).exp(), (-0.2f32).exp(), 1.0);
+        assert!((medium.transmittance(2.0) - expected).len() < 1e-6);
+    }
 }
@@ -0,0 +1,75 @@
+use crate::math::Vec3;
+use crate::mtl::{Medium, ScatterRecord, Scatterable};
+use crate::trace::{Hit, Ray};
+
+/// Emissive adds the light of an emitting material, e.g. LightDiffuse, to
+/// another material, which keeps scattering as before. It is used for glowing
+/// surfaces that also reflect, like a screen behind glass:
+///     mat - material that scatters the rays
+///     emission - material whose emitted light is added
+pub struct Emissive<M: Scatterable, E: Scatterable> {
+    mat: M,
+    emission: E,
+}
+
+impl<M: Scatterable, E: Scatterable> Emissive<M, E> {
+    pub fn new(mat: M, emission: E) -> Emissive<M, E> {
+        Emissive { mat, emission }
+    }
+}
+
+impl<M: Scatterable, E: Scatterable> Scatterable for Emissive<M, E> {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        self.mat.scatter(r, hit)
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> Vec3 {
+        self.mat.eval(hit, wi, wo)
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> f32 {
+        self.mat.pdf(hit, wi, wo)
+    }
+
+    fn emitted(&self, r: &Ray, hit: &Hit) -> Vec3 {
+        self.mat.emitted(r, hit) + self.emission.emitted(r, hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive() || self.emission.is_emissive()
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.mat.medium()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::{LightDiffuse, Metal};
+    use crate::tex::ConstTexture;
+
+    #[test]
+    fn test_emissive_metal() {
+        let light = LightDiffuse::new(ConstTexture::new(Vec3(1.0, 0.5, 0.0)), 2.0, false);
+        let mat = Emissive::new(Metal::new(Vec3(0.9, 0.9, 0.9), 0.0), light);
+        assert!(mat.is_emissive());
+
+        let hit = Hit::new(
+            1.0,
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            &mat,
+            0.0,
+            0.0,
+        );
+        let r = Ray::new(Vec3(-1.0, 1.0, 0.0), Vec3(1.0, -1.0, 0.0), 0.0);
+        assert_eq!(mat.emitted(&r, &hit), Vec3(2.0, 1.0, 0.0));
+
+        // the mirror still reflects the ray
+        let rec = mat.scatter(&r, &hit).unwrap();
+        assert!(rec.specular);
+        assert!((rec.ray.direction().as_unit() - Vec3(1.0, 1.0, 0.0).as_unit()).len() < 1e-5);
+    }
+}
@@ -1,8 +1,10 @@
+use std::f32::consts::PI;
+
 use crate::math::Vec3;
-use crate::mtl::Scatterable;
+use crate::mtl::{ScatterRecord, Scatterable};
 use crate::tex::Wrappable;
 use crate::trace::{Hit, Ray};
-use crate::utils::rng::rand_in_unit_sphere;
+use crate::utils::rng::rand_unit_vector;
 
 pub struct Isotropic<W: Wrappable> {
     albedo: W,
@@ -15,10 +17,20 @@ impl<W: Wrappable> Isotropic<W> {
 }
 
 impl<W: Wrappable> Scatterable for Isotropic<W> {
-    fn scatter(&self, r: &Ray, hit: Hit) -> Option<(Ray, Vec3)> {
-        Some((
-            Ray::new(hit.p(), rand_in_unit_sphere(), r.time()),
-            self.albedo.value(hit.u(), hit.v(), hit.p()),
-        ))
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        Some(ScatterRecord {
+            ray: Ray::new(hit.p(), rand_unit_vector(), r.time()),
+            attenuation: self.albedo.value(hit.u(), hit.v(), hit.p()),
+            pdf: 1.0 / (4.0 * PI),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, hit: &Hit, _: Vec3, _: Vec3) -> Vec3 {
+        self.albedo.value(hit.u(), hit.v(), hit.p()) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _: &Hit, _: Vec3, _: Vec3) -> f32 {
+        1.0 / (4.0 * PI)
     }
 }
@@ -1,8 +1,10 @@
-use super::Scatterable;
+use std::f32::consts::PI;
+
+use super::{ScatterRecord, Scatterable};
 use crate::math::Vec3;
 use crate::tex::Wrappable;
 use crate::trace::{Hit, Ray};
-use crate::utils::rng::rand_in_unit_sphere;
+use crate::utils::rng::rand_unit_vector;
 
 #[derive(Clone)]
 pub struct Lambertian<W: Wrappable> {
@@ -16,11 +18,56 @@ impl<W: Wrappable> Lambertian<W> {
 }
 
 impl<W: Wrappable> Scatterable for Lambertian<W> {
-    fn scatter(&self, r: &Ray, hit: Hit) -> Option<(Ray, Vec3)> {
-        let target = hit.p() + hit.n() + rand_in_unit_sphere();
-        Some((
-            Ray::new(hit.p(), target - hit.p(), r.time()),
-            self.albedo.value(hit.u(), hit.v(), hit.p()),
-        ))
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        // n + a random unit vector is distributed with cos(theta) around n
+        let mut dir = hit.n() + rand_unit_vector();
+        if dir.sq_len() < 1e-8 {
+            dir = hit.n();
+        }
+        // the cosine of the BRDF cancels with the pdf
+        Some(ScatterRecord {
+            ray: Ray::new(hit.p(), dir, r.time()),
+            attenuation: self.albedo.value(hit.u(), hit.v(), hit.p()),
+            pdf: self.pdf(hit, dir.as_unit(), -r.direction().as_unit()),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, _: Vec3) -> Vec3 {
+        let cos = hit.n().dot(wi).max(0.0);
+        self.albedo.value(hit.u(), hit.v(), hit.p()) * (cos / PI)
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, _: Vec3) -> f32 {
+        hit.n().dot(wi).max(0.0) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tex::ConstTexture;
+
+    #[test]
+    fn test_lambertian_scatter() {
+        let mat = Lambertian::new(ConstTexture::new(Vec3(0.5, 0.25, 1.0)));
+        let n = Vec3(0.0, 1.0, 0.0);
+        let hit = Hit::new(1.0, Vec3(0.0, 0.0, 0.0), n, &mat, 0.0, 0.0);
+        let r = Ray::new(Vec3(0.0, 1.0, -1.0), Vec3(0.0, -1.0, 1.0), 0.0);
+        let wo = -r.direction().as_unit();
+
+        for _ in 0..100 {
+            let rec = mat.scatter(&r, &hit).unwrap();
+            let wi = rec.ray.direction().as_unit();
+            assert!(!rec.specular);
+            assert!(wi.dot(n) >= 0.0);
+            assert!((rec.pdf - mat.pdf(&hit, wi, wo)).abs() < 1e-5);
+
+            // attenuation is eval / pdf
+            if rec.pdf > 1e-3 {
+                let expected = mat.eval(&hit, wi, wo) / rec.pdf;
+                assert!((rec.attenuation - expected).len() < 1e-3);
+            }
+        }
     }
 }
@@ -1,24 +1,92 @@
 use crate::math::Vec3;
-use crate::mtl::Scatterable;
+use crate::mtl::{ScatterRecord, Scatterable};
 use crate::tex::Wrappable;
 use crate::trace::{Hit, Ray};
+use crate::utils::color::blackbody;
 
+/// LightDiffuse is a material that emits light equally into all directions
+/// and does not scatter:
+///     emit - radiance of the surface
+///     intensity - factor for the radiance
+///     two_sided - whether the surface emits to both sides, otherwise only to
+///                 the side its normal points to
 pub struct LightDiffuse<W: Wrappable> {
     emit: W,
+    scale: Vec3,
+    two_sided: bool,
 }
 
 impl<W: Wrappable> LightDiffuse<W> {
-    pub fn new(emit: W) -> LightDiffuse<W> {
-        LightDiffuse { emit }
+    /// Returns a new light that emits the texture scaled by intensity.
+    pub fn new(emit: W, intensity: f32, two_sided: bool) -> LightDiffuse<W> {
+        LightDiffuse {
+            emit,
+            scale: Vec3(intensity, intensity, intensity),
+            two_sided,
+        }
+    }
+
+    /// Returns a new light with the color of a blackbody at the temperature in
+    /// Kelvin. The color has a luminance of 1 and is tinted with the texture.
+    pub fn blackbody(
+        emit: W,
+        temperature: f32,
+        intensity: f32,
+        two_sided: bool,
+    ) -> LightDiffuse<W> {
+        LightDiffuse {
+            emit,
+            scale: intensity * blackbody(temperature),
+            two_sided,
+        }
     }
 }
 
 impl<W: Wrappable> Scatterable for LightDiffuse<W> {
-    fn scatter(&self, _: &Ray, _: Hit) -> Option<(Ray, Vec3)> {
+    fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
         None
     }
 
-    fn emitted(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
-        self.emit.value(u, v, p)
+    fn emitted(&self, r: &Ray, hit: &Hit) -> Vec3 {
+        if !self.two_sided && r.direction().dot(hit.ng()) >= 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+        self.scale * self.emit.value(hit.u(), hit.v(), hit.p())
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tex::ConstTexture;
+
+    #[test]
+    fn test_light_sides() {
+        let one_sided = LightDiffuse::new(ConstTexture::new(Vec3(1.0, 2.0, 3.0)), 2.0, false);
+        let hit = Hit::new(
+            1.0,
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            &one_sided,
+            0.0,
+            0.0,
+        );
+        let from_front = Ray::new(Vec3(0.0, 1.0, 0.0), Vec3(0.0, -1.0, 0.0), 0.0);
+        let from_back = Ray::new(Vec3(0.0, -1.0, 0.0), Vec3(0.0, 1.0, 0.0), 0.0);
+        assert_eq!(one_sided.emitted(&from_front, &hit), Vec3(2.0, 4.0, 6.0));
+        assert_eq!(one_sided.emitted(&from_back, &hit), Vec3(0.0, 0.0, 0.0));
+
+        let two_sided = LightDiffuse::new(ConstTexture::new(Vec3(1.0, 2.0, 3.0)), 1.0, true);
+        assert_eq!(two_sided.emitted(&from_back, &hit), Vec3(1.0, 2.0, 3.0));
+
+        let warm =
+            LightDiffuse::blackbody(ConstTexture::new(Vec3(1.0, 1.0, 1.0)), 2700.0, 5.0, true);
+        let col = warm.emitted(&from_front, &hit);
+        assert!((col.luminance() - 5.0).abs() < 1e-3);
+        assert!(col.0 > col.2);
     }
 }
@@ -0,0 +1,33 @@
+use crate::math::Vec3;
+
+use super::utils as mtl_utils;
+
+/// Medium describes what fills the inside of a closed object with a
+/// dielectric material, e.g. water or glass:
+///     ior - index of refraction
+///     absorption - fraction of the light absorbed per unit of distance, for
+///                  red, green and blue
+///     priority - decides which medium fills the space where objects overlap,
+///                the one with the highest priority wins
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Medium {
+    pub ior: f32,
+    pub absorption: Vec3,
+    pub priority: u32,
+}
+
+impl Medium {
+    pub fn new(ior: f32, absorption: Vec3, priority: u32) -> Medium {
+        Medium {
+            ior,
+            absorption,
+            priority,
+        }
+    }
+
+    /// Returns the fraction of light that is not absorbed after travelling
+    /// the distance dist through the medium, following the Beer-Lambert law.
+    pub fn transmittance(&self, dist: f32) -> Vec3 {
+        mtl_utils::transmittance(self.absorption, dist)
+    }
+}
@@ -1,10 +1,17 @@
+use std::f32::consts::PI;
+
 use crate::math::Vec3;
 use crate::trace::{Hit, Ray};
 use crate::utils::rng::rand_in_unit_sphere;
 
 use super::utils as mtl_utils;
-use super::Scatterable;
+use super::{ScatterRecord, Scatterable};
 
+/// Metal reflects rays to the mirror direction moved by a random point in a
+/// ball, whose radius is the fuzz. With fuzz 0 it is a perfect mirror:
+///     albedo - color of the reflections
+///     fuzz - roughness of the surface in [0, 1]
+/// Reflections that would go below the surface are absorbed.
 pub struct Metal {
     albedo: Vec3,
     fuzz: f32,
@@ -17,20 +24,136 @@ impl Metal {
             fuzz: f32::min(fuzz, 1.0),
         }
     }
+
+    /// Returns the density of the unit direction dir for the points chosen
+    /// uniformly in the fuzz ball around the unit mirror direction. It is the
+    /// volume of the ball along dir, integral of t^2 dt, over that of the ball.
+    fn density(&self, reflected: Vec3, dir: Vec3) -> f32 {
+        // dir * t enters and leaves the ball at t = c -+ sqrt(disc)
+        let c = dir.dot(reflected);
+        let disc = c * c - 1.0 + self.fuzz * self.fuzz;
+        if disc <= 0.0 {
+            return 0.0;
+        }
+        let (t0, t1) = (c - disc.sqrt(), c + disc.sqrt());
+        if t1 <= 0.0 {
+            return 0.0;
+        }
+        // the origin lies on the ball for fuzz 1
+        let volume = if t0 > 0.0 {
+            (t1 - t0) * (t1 * t1 + t1 * t0 + t0 * t0)
+        } else {
+            t1 * t1 * t1
+        };
+        volume / (4.0 * PI * self.fuzz.powi(3))
+    }
 }
 
 impl Scatterable for Metal {
-    fn scatter(&self, r: &Ray, hit: Hit) -> Option<(Ray, Vec3)> {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
         let reflected = mtl_utils::reflect(r.direction().as_unit(), hit.n());
-        let scattered = Ray::new(
-            hit.p(),
-            reflected + self.fuzz * rand_in_unit_sphere(),
-            r.time(),
+        if self.fuzz <= 0.0 {
+            if reflected.dot(hit.n()) <= 0.0 {
+                return None;
+            }
+            let scattered = Ray::new(hit.p(), reflected, r.time());
+            return Some(ScatterRecord::specular(scattered, self.albedo));
+        }
+
+        let dir = reflected + self.fuzz * rand_in_unit_sphere();
+        if dir.dot(hit.n()) <= 0.0 {
+            return None;
+        }
+        let dir = dir.as_unit();
+        // eval / pdf, the density cancels out
+        Some(ScatterRecord {
+            ray: Ray::new(hit.p(), dir, r.time()),
+            attenuation: self.albedo,
+            pdf: self.density(reflected, dir),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> Vec3 {
+        if self.fuzz <= 0.0 || wi.dot(hit.n()) <= 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+        let reflected = mtl_utils::reflect(-wo, hit.n());
+        self.albedo * self.density(reflected, wi)
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> f32 {
+        if self.fuzz <= 0.0 {
+            return 0.0;
+        }
+        let reflected = mtl_utils::reflect(-wo, hit.n());
+        self.density(reflected, wi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metal_mirror() {
+        let mat = Metal::new(Vec3(0.9, 0.9, 0.9), 0.0);
+        let hit = Hit::new(
+            1.0,
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            &mat,
+            0.0,
+            0.0,
         );
-        if scattered.direction().dot(hit.n()) > 0.0 {
-            Some((scattered, self.albedo))
-        } else {
-            None
+        let r = Ray::new(Vec3(-1.0, 1.0, 0.0), Vec3(1.0, -1.0, 0.0), 0.0);
+
+        let rec = mat.scatter(&r, &hit).unwrap();
+        assert!(rec.specular);
+        assert!((rec.ray.direction().as_unit() - Vec3(1.0, 1.0, 0.0).as_unit()).len() < 1e-5);
+    }
+
+    #[test]
+    fn test_metal_fuzz() {
+        let mat = Metal::new(Vec3(0.9, 0.5, 0.1), 0.3);
+        let hit = Hit::new(
+            1.0,
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            &mat,
+            0.0,
+            0.0,
+        );
+        let r = Ray::new(Vec3(-1.0, 1.0, 0.0), Vec3(1.0, -1.0, 0.0), 0.0);
+        let wo = -r.direction().as_unit();
+
+        for _ in 0..100 {
+            let rec = match mat.scatter(&r, &hit) {
+                Some(rec) => rec,
+                None => continue,
+            };
+            let wi = rec.ray.direction().as_unit();
+            assert!(!rec.specular);
+            assert!((rec.pdf - mat.pdf(&hit, wi, wo)).abs() < 1e-3 * rec.pdf);
+
+            let expected = mat.eval(&hit, wi, wo) / rec.pdf;
+            assert!((rec.attenuation - expected).len() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_metal_density() {
+        // the density of the directions integrates to 1 over the sphere
+        let reflected = Vec3(1.0, 1.0, 0.0).as_unit();
+        for &fuzz in &[0.8, 1.0] {
+            let mat = Metal::new(Vec3(1.0, 1.0, 1.0), fuzz);
+            let n = 200_000;
+            let mut sum = 0.0;
+            for _ in 0..n {
+                let dir = rand_in_unit_sphere().as_unit();
+                sum += mat.density(reflected, dir) * 4.0 * PI;
+            }
+            assert!((sum / n as f32 - 1.0).abs() < 0.03);
         }
     }
 }
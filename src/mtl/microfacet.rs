@@ -0,0 +1,120 @@
+use std::f32::consts::PI;
+
+use crate::math::Vec3;
+
+// below this roughness the surface is treated as perfectly smooth
+const MIN_ALPHA: f32 = 1e-3;
+
+/// GGX is the Trowbridge-Reitz distribution of microfacet normals with the
+/// Smith shadowing function. Directions are given in the local frame of the
+/// surface with the normal along z:
+///     alpha - width of the distribution
+pub struct GGX {
+    alpha: f32,
+}
+
+impl GGX {
+    /// Returns the distribution for the perceptual roughness in [0, 1].
+    pub fn new(roughness: f32) -> GGX {
+        GGX {
+            alpha: roughness.clamp(0.0, 1.0).powi(2),
+        }
+    }
+
+    /// Returns whether the surface is smooth enough to be a mirror.
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < MIN_ALPHA
+    }
+
+    /// Returns the density of microfacets with the normal h.
+    pub fn d(&self, h: Vec3) -> f32 {
+        if h.2 <= 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha * self.alpha;
+        let t = (h.0 * h.0 + h.1 * h.1) / a2 + h.2 * h.2;
+        1.0 / (PI * a2 * t * t)
+    }
+
+    /// Returns the Smith auxiliary function for the direction w.
+    fn lambda(&self, w: Vec3) -> f32 {
+        if w.2 == 0.0 {
+            return f32::MAX;
+        }
+        let a2 = self.alpha * self.alpha * (w.0 * w.0 + w.1 * w.1) / (w.2 * w.2);
+        ((1.0 + a2).sqrt() - 1.0) / 2.0
+    }
+
+    /// Returns the fraction of microfacets visible from the direction w.
+    pub fn g1(&self, w: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Returns the fraction of microfacets visible from both wo and wi, with
+    /// the height-correlated shadowing function.
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Returns a microfacet normal sampled from the normals visible from wo,
+    /// which must be above the surface, for the random numbers u1 and u2 in
+    /// [0, 1).
+    pub fn sample_visible(&self, wo: Vec3, u1: f32, u2: f32) -> Vec3 {
+        // stretch to the configuration with roughness 1
+        let v = Vec3(self.alpha * wo.0, self.alpha * wo.1, wo.2).as_unit();
+
+        // sample the projection of the hemisphere around v
+        let phi = 2.0 * PI * u1;
+        let z = (1.0 - u2) * (1.0 + v.2) - v.2;
+        let sin_theta = (1.0 - z * z).clamp(0.0, 1.0).sqrt();
+        let c = Vec3(sin_theta * phi.cos(), sin_theta * phi.sin(), z);
+        let h = c + v;
+
+        // unstretch
+        Vec3(self.alpha * h.0, self.alpha * h.1, h.2.max(0.0)).as_unit()
+    }
+
+    /// Returns the density with which sample_visible chooses h for wo.
+    pub fn pdf_visible(&self, wo: Vec3, h: Vec3) -> f32 {
+        if wo.2 <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * wo.dot(h).max(0.0) * self.d(h) / wo.2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rng::uniform_in_range;
+
+    #[test]
+    fn test_ggx_normalized() {
+        // the projected areas of the microfacets add up to that of the surface
+        let ggx = GGX::new(0.6);
+        let n = 200_000;
+        let mut sum = 0.0;
+        for _ in 0..n {
+            // uniform on the hemisphere
+            let z = uniform_in_range(0.0f32, 1.0);
+            let phi = 2.0 * PI * uniform_in_range(0.0f32, 1.0);
+            let r = (1.0 - z * z).sqrt();
+            let h = Vec3(r * phi.cos(), r * phi.sin(), z);
+            sum += ggx.d(h) * h.2 * 2.0 * PI;
+        }
+        assert!((sum / n as f32 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_ggx_visible() {
+        let ggx = GGX::new(0.5);
+        let wo = Vec3(0.6, 0.3, 0.5).as_unit();
+        for _ in 0..100 {
+            let h = ggx.sample_visible(wo, uniform_in_range(0.0, 1.0), uniform_in_range(0.0, 1.0));
+            assert!((h.len() - 1.0).abs() < 1e-4);
+            assert!(h.2 >= 0.0 && wo.dot(h) >= -1e-4);
+        }
+        assert!(GGX::new(0.0).is_smooth());
+        assert!(!GGX::new(0.1).is_smooth());
+    }
+}
@@ -1,14 +1,25 @@
+pub use conductor::Conductor;
 pub use dielectric::Dielectric;
+pub use emissive::Emissive;
 pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use light_source::LightDiffuse;
+pub use medium::Medium;
 pub use metal::Metal;
-pub use scatterable::Scatterable;
+pub use microfacet::GGX;
+pub use rough_dielectric::RoughDielectric;
+pub use scatterable::{ScatterRecord, Scatterable};
+pub use utils::{balance_heuristic, mixture_pdf, power_heuristic};
 
+mod conductor;
 mod dielectric;
+mod emissive;
 mod isotropic;
 mod lambertian;
 mod light_source;
+mod medium;
 mod metal;
+mod microfacet;
+mod rough_dielectric;
 mod scatterable;
 mod utils;
@@ -0,0 +1,269 @@
+use crate::math::{Vec3, ONB};
+use crate::tex::Wrappable;
+use crate::trace::{Hit, Ray};
+use crate::utils::rng::uniform_in_range;
+
+use super::microfacet::GGX;
+use super::utils as mtl_utils;
+use super::{Medium, ScatterRecord, Scatterable};
+
+/// RoughDielectric is glass with microfacets distributed by GGX, which
+/// reflects and refracts rays with the exact Fresnel equations, e.g. frosted
+/// glass or ice:
+///     medium - medium inside the glass, i.e. opposite the normal, whose index
+///              of refraction is relative to hit.outside_ior
+///     albedo - tint of the reflected and refracted light
+///     ggx - distribution of the microfacets
+///
+/// Like Dielectric, radiance is not scaled by the squared ratio of the
+/// indices when refracted, which cancels out for closed objects.
+pub struct RoughDielectric<W: Wrappable> {
+    medium: Medium,
+    albedo: W,
+    ggx: GGX,
+}
+
+impl<W: Wrappable> RoughDielectric<W> {
+    /// Returns glass with the index of refraction ior and the perceptual
+    /// roughness in [0, 1].
+    pub fn new(ior: f32, roughness: f32, albedo: W) -> RoughDielectric<W> {
+        let medium = Medium::new(ior, Vec3(0.0, 0.0, 0.0), 0);
+        RoughDielectric::with_medium(medium, roughness, albedo)
+    }
+
+    /// Returns glass filled with the medium, which may absorb light and have
+    /// a priority over the media of overlapping objects.
+    pub fn with_medium(medium: Medium, roughness: f32, albedo: W) -> RoughDielectric<W> {
+        RoughDielectric {
+            medium,
+            albedo,
+            ggx: GGX::new(roughness),
+        }
+    }
+
+    /// Returns the frame of the surface with the normal on the side of wo,
+    /// and the index of refraction of the other side relative to that of wo.
+    fn frame(&self, hit: &Hit, wo: Vec3) -> (ONB, f32) {
+        let eta = self.medium.ior / hit.outside_ior();
+        if wo.dot(hit.ng()) >= 0.0 {
+            (ONB::from_w(hit.n()), eta)
+        } else {
+            (ONB::from_w(-hit.n()), 1.0 / eta)
+        }
+    }
+
+    /// Returns whether rays only scatter into delta distributions, which is
+    /// the case for smooth glass and for media with the same index on either
+    /// side, between which rays pass straight through.
+    fn is_specular(&self, eta: f32) -> bool {
+        self.ggx.is_smooth() || (eta - 1.0).abs() < 1e-4
+    }
+
+    /// Returns the half vector of the pair of local directions, facing wo, or
+    /// None if it is not defined.
+    fn half_vector(wi: Vec3, wo: Vec3, eta: f32) -> Option<Vec3> {
+        let h = if wi.2 > 0.0 { wi + wo } else { wo + eta * wi };
+        if h.dot(h) < 1e-12 {
+            return None;
+        }
+        let h = if h.2 < 0.0 { -h.as_unit() } else { h.as_unit() };
+
+        // microfacets seen from behind by either direction do not scatter
+        if wo.dot(h) <= 0.0 || wi.dot(h) * wi.2 <= 0.0 {
+            return None;
+        }
+        Some(h)
+    }
+}
+
+/// Returns the direction wo refracted through the microfacet h, where eta is
+/// the index behind the microfacet relative to the one in front of it.
+fn refract(wo: Vec3, h: Vec3, eta: f32) -> Vec3 {
+    let cos_i = wo.dot(h);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    let cos_t = (1.0 - sin2_t).max(0.0).sqrt();
+    -wo / eta + (cos_i / eta - cos_t) * h
+}
+
+impl<W: Wrappable> Scatterable for RoughDielectric<W> {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        let (onb, eta) = self.frame(hit, -r.direction().as_unit());
+        let wo = onb.coords(-r.direction().as_unit());
+        if wo.2 <= 0.0 {
+            return None;
+        }
+        let albedo = self.albedo.value(hit.u(), hit.v(), hit.p());
+
+        let h = if self.is_specular(eta) {
+            Vec3(0.0, 0.0, 1.0)
+        } else {
+            self.ggx
+                .sample_visible(wo, uniform_in_range(0.0, 1.0), uniform_in_range(0.0, 1.0))
+        };
+        let cos_h = wo.dot(h);
+        if cos_h <= 0.0 {
+            return None;
+        }
+
+        // choose reflection with the probability of the Fresnel reflectance,
+        // which is 1 for total internal reflection
+        let fresnel = mtl_utils::fresnel_dielectric(cos_h, eta);
+        let reflect = uniform_in_range(0.0, 1.0) < fresnel;
+        let wi = if reflect {
+            2.0 * cos_h * h - wo
+        } else {
+            refract(wo, h, eta)
+        };
+        if (wi.2 > 0.0) != reflect {
+            return None;
+        }
+        let ray = Ray::new(hit.p(), onb.local(wi), r.time());
+
+        if self.is_specular(eta) {
+            return Some(ScatterRecord::specular(ray, albedo));
+        }
+
+        // eval / pdf, the Fresnel term, D and the Jacobians cancel out
+        let pdf = if reflect {
+            fresnel * self.ggx.pdf_visible(wo, h) / (4.0 * cos_h)
+        } else {
+            let denom = wi.dot(h) + cos_h / eta;
+            (1.0 - fresnel) * self.ggx.pdf_visible(wo, h) * wi.dot(h).abs() / (denom * denom)
+        };
+        Some(ScatterRecord {
+            ray,
+            attenuation: albedo * (self.ggx.g(wo, wi) / self.ggx.g1(wo)),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> Vec3 {
+        let (onb, eta) = self.frame(hit, wo);
+        let (wi, wo) = (onb.coords(wi), onb.coords(wo));
+        if self.is_specular(eta) || wo.2 <= 0.0 || wi.2 == 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+        let h = match Self::half_vector(wi, wo, eta) {
+            Some(h) => h,
+            None => return Vec3(0.0, 0.0, 0.0),
+        };
+
+        let albedo = self.albedo.value(hit.u(), hit.v(), hit.p());
+        let cos_h = wo.dot(h);
+        let fresnel = mtl_utils::fresnel_dielectric(cos_h, eta);
+        let dg = self.ggx.d(h) * self.ggx.g(wo, wi);
+        if wi.2 > 0.0 {
+            albedo * (fresnel * dg / (4.0 * wo.2))
+        } else {
+            let denom = wi.dot(h) + cos_h / eta;
+            let jacobian = wi.dot(h).abs() * cos_h / (denom * denom);
+            albedo * ((1.0 - fresnel) * dg * jacobian / wo.2)
+        }
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> f32 {
+        let (onb, eta) = self.frame(hit, wo);
+        let (wi, wo) = (onb.coords(wi), onb.coords(wo));
+        if self.is_specular(eta) || wo.2 <= 0.0 || wi.2 == 0.0 {
+            return 0.0;
+        }
+        let h = match Self::half_vector(wi, wo, eta) {
+            Some(h) => h,
+            None => return 0.0,
+        };
+
+        let cos_h = wo.dot(h);
+        let fresnel = mtl_utils::fresnel_dielectric(cos_h, eta);
+        if wi.2 > 0.0 {
+            fresnel * self.ggx.pdf_visible(wo, h) / (4.0 * cos_h)
+        } else {
+            let denom = wi.dot(h) + cos_h / eta;
+            (1.0 - fresnel) * self.ggx.pdf_visible(wo, h) * wi.dot(h).abs() / (denom * denom)
+        }
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(self.medium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tex::ConstTexture;
+
+    fn glass(roughness: f32) -> RoughDielectric<ConstTexture> {
+        RoughDielectric::new(1.5, roughness, ConstTexture::new(Vec3(1.0, 1.0, 1.0)))
+    }
+
+    fn hit<W: Wrappable>(mat: &RoughDielectric<W>) -> Hit<'_> {
+        Hit::new(1.0, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0), mat, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_rough_dielectric_sampling() {
+        let mat = glass(0.4);
+        let hit = hit(&mat);
+        // from outside and from inside the glass
+        for dir in &[Vec3(1.0, -2.0, 0.5), Vec3(0.5, 2.0, -0.3)] {
+            let r = Ray::new(-*dir, *dir, 0.0);
+            let wo = -r.direction().as_unit();
+            let mut refracted = 0;
+            for _ in 0..200 {
+                let rec = match mat.scatter(&r, &hit) {
+                    Some(rec) => rec,
+                    None => continue,
+                };
+                let wi = rec.ray.direction().as_unit();
+                if wi.dot(hit.n()) * wo.dot(hit.n()) < 0.0 {
+                    refracted += 1;
+                }
+                assert!(!rec.specular);
+                assert!((rec.pdf - mat.pdf(&hit, wi, wo)).abs() < 1e-3 * rec.pdf);
+
+                let expected = mat.eval(&hit, wi, wo) / rec.pdf;
+                assert!((rec.attenuation - expected).len() < 1e-3);
+            }
+            assert!(refracted > 100);
+        }
+    }
+
+    #[test]
+    fn test_rough_dielectric_tir() {
+        // at grazing angles inside the glass all light is reflected
+        let mat = glass(0.0);
+        let hit = hit(&mat);
+        let r = Ray::new(Vec3(-1.0, -0.2, 0.0), Vec3(1.0, 0.2, 0.0), 0.0);
+        for _ in 0..20 {
+            let rec = mat.scatter(&r, &hit).unwrap();
+            assert!(rec.specular);
+            assert!((rec.ray.direction().as_unit() - Vec3(1.0, -0.2, 0.0).as_unit()).len() < 1e-5);
+        }
+
+        // and straight on, most of it is refracted without bending
+        let r = Ray::new(Vec3(0.0, 1.0, 0.0), Vec3(0.0, -1.0, 0.0), 0.0);
+        let refracted = (0..1000)
+            .filter(|_| mat.scatter(&r, &hit).unwrap().ray.direction().1 < -0.999)
+            .count();
+        assert!(refracted > 900);
+    }
+
+    #[test]
+    fn test_rough_dielectric_energy() {
+        // without absorption only shadowing by microfacets loses energy
+        let mat = glass(0.3);
+        let hit = hit(&mat);
+        let r = Ray::new(Vec3(0.0, 1.0, 0.0), Vec3(0.3, -1.0, 0.0), 0.0);
+
+        let n = 10_000;
+        let mut sum = 0.0;
+        for _ in 0..n {
+            if let Some(rec) = mat.scatter(&r, &hit) {
+                sum += rec.attenuation.1;
+            }
+        }
+        let albedo = sum / n as f32;
+        assert!(albedo > 0.9 && albedo <= 1.0);
+    }
+}
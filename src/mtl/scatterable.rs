@@ -1,11 +1,102 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
+use crate::mtl::Medium;
 use crate::trace::{Hit, Ray};
 
-pub trait Scatterable {
-    fn scatter(&self, r: &Ray, hit: Hit) -> Option<(Ray, Vec3)>;
+/// ScatterRecord describes how a material scattered a ray:
+///     ray - the scattered ray
+///     attenuation - factor for the light arriving along the scattered ray,
+///                   i.e. eval / pdf for the scattered direction
+///     pdf - density, with respect to solid angle, with which the direction
+///           of the scattered ray was chosen
+///     specular - whether the direction was chosen from a delta
+///                distribution, e.g. a mirror reflection. Then eval and pdf
+///                can not be used for it and pdf is 0.
+pub struct ScatterRecord {
+    pub ray: Ray,
+    pub attenuation: Vec3,
+    pub pdf: f32,
+    pub specular: bool,
+}
+
+impl ScatterRecord {
+    /// Returns a record for a ray that was scattered from a delta distribution.
+    pub fn specular(ray: Ray, attenuation: Vec3) -> ScatterRecord {
+        ScatterRecord {
+            ray,
+            attenuation,
+            pdf: 0.0,
+            specular: true,
+        }
+    }
+}
+
+/// Scatterable is a trait for materials. Directions passed to eval and pdf are
+/// unit vectors pointing away from the hit:
+///     wi - direction the light arrives from
+///     wo - direction the light leaves to, i.e. back along the incoming ray
+pub trait Scatterable: Send + Sync {
+    /// Returns how the incoming ray is scattered at the hit, or None if it is
+    /// absorbed.
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord>;
+
+    /// Returns the scattering function for the pair of directions, including
+    /// the cosine of wi to the normal for surfaces. It is 0 for materials that
+    /// only scatter into delta distributions.
+    fn eval(&self, _hit: &Hit, _wi: Vec3, _wo: Vec3) -> Vec3 {
+        Vec3(0.0, 0.0, 0.0)
+    }
+
+    /// Returns the density, with respect to solid angle, with which scatter
+    /// chooses wi for the ray arriving from wo.
+    fn pdf(&self, _hit: &Hit, _wi: Vec3, _wo: Vec3) -> f32 {
+        0.0
+    }
 
-    fn emitted(&self, _: f32, _: f32, _: Vec3) -> Vec3 {
+    /// Returns the light emitted at the hit back along the ray, which depends
+    /// on the side of the surface that was hit for one-sided emitters.
+    fn emitted(&self, _r: &Ray, _hit: &Hit) -> Vec3 {
         // Black (non-emitting) by default
         Vec3(0.0, 0.0, 0.0)
     }
+
+    /// Returns whether the material emits light. Objects with emitting
+    /// materials are sampled directly as light sources.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Returns the medium inside objects of the material, for dielectrics
+    /// whose interfaces the integrator tracks. The index of refraction on the
+    /// other side of the interface is then given by hit.outside_ior.
+    fn medium(&self) -> Option<Medium> {
+        None
+    }
+}
+
+impl<S: Scatterable + ?Sized> Scatterable for Arc<S> {
+    fn scatter(&self, r: &Ray, hit: &Hit) -> Option<ScatterRecord> {
+        self.as_ref().scatter(r, hit)
+    }
+
+    fn eval(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> Vec3 {
+        self.as_ref().eval(hit, wi, wo)
+    }
+
+    fn pdf(&self, hit: &Hit, wi: Vec3, wo: Vec3) -> f32 {
+        self.as_ref().pdf(hit, wi, wo)
+    }
+
+    fn emitted(&self, r: &Ray, hit: &Hit) -> Vec3 {
+        self.as_ref().emitted(r, hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.as_ref().is_emissive()
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.as_ref().medium()
+    }
 }
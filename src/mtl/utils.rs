@@ -19,3 +19,132 @@ pub fn schlick(rfn_ind: f32, cos: f32) -> f32 {
     let r0 = ((1.0 - rfn_ind) / (1.0 + rfn_ind)).powi(2);
     r0 + (1.0 - r0) * (1.0 - cos).powi(5)
 }
+
+/// Returns the fraction of light reflected at the smooth interface between two
+/// dielectrics, for light arriving at the cosine cos_i to the normal, where eta
+/// is the index of refraction behind the interface over the one in front of
+/// it. It is 1 for total internal reflection.
+pub fn fresnel_dielectric(cos_i: f32, eta: f32) -> f32 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    let rs = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let rp = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
+
+/// Returns the fraction of light reflected by a conductor with the complex
+/// index of refraction eta + ik, for light arriving at the cosine cos_i to the
+/// normal from a medium with index 1.
+pub fn fresnel_conductor(cos_i: f32, eta: f32, k: f32) -> f32 {
+    let cos2 = cos_i.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+    let (eta2, k2) = (eta * eta, k * k);
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let t2 = 2.0 * cos_i.clamp(0.0, 1.0) * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rp + rs)
+}
+
+/// Returns the fraction of light that is not absorbed after travelling the
+/// distance dist through a medium with the given absorption coefficients.
+/// The distance may be infinite, e.g. for rays that escape the scene: the
+/// channels that are absorbed then vanish and the others pass unchanged.
+pub fn transmittance(absorption: Vec3, dist: f32) -> Vec3 {
+    let channel = |a: f32| if a == 0.0 { 1.0 } else { (-a * dist).exp() };
+    Vec3(
+        channel(absorption.0),
+        channel(absorption.1),
+        channel(absorption.2),
+    )
+}
+
+/// Returns the density of a mixture that picks one of the given densities with
+/// equal probability, e.g. one of the light sources of a scene.
+pub fn mixture_pdf<I: IntoIterator<Item = f32>>(pdfs: I) -> f32 {
+    let (sum, n) = pdfs
+        .into_iter()
+        .fold((0.0, 0), |(sum, n), pdf| (sum + pdf, n + 1));
+    if n == 0 {
+        0.0
+    } else {
+        sum / n as f32
+    }
+}
+
+/// Returns the weight for a sample taken with density f_pdf, when the
+/// direction could also have been sampled with density g_pdf.
+pub fn balance_heuristic(f_pdf: f32, g_pdf: f32) -> f32 {
+    if f_pdf + g_pdf <= 0.0 {
+        return 0.0;
+    }
+    f_pdf / (f_pdf + g_pdf)
+}
+
+/// Returns the weight for a sample taken with density f_pdf, when the
+/// direction could also have been sampled with density g_pdf. Compared to the
+/// balance heuristic, it favors the strategy with the higher density.
+pub fn power_heuristic(f_pdf: f32, g_pdf: f32) -> f32 {
+    let (f2, g2) = (f_pdf * f_pdf, g_pdf * g_pdf);
+    if f2 + g2 <= 0.0 {
+        return 0.0;
+    }
+    f2 / (f2 + g2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixture_pdf() {
+        assert_eq!(mixture_pdf(vec![]), 0.0);
+        assert_eq!(mixture_pdf(vec![1.0, 0.0, 2.0]), 1.0);
+    }
+
+    #[test]
+    fn test_fresnel() {
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-6);
+        assert!((fresnel_dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-6);
+        assert_eq!(fresnel_dielectric(0.3, 1.0 / 1.5), 1.0);
+        assert!((fresnel_dielectric(0.0, 1.5) - 1.0).abs() < 1e-6);
+
+        // ((n - 1)^2 + k^2) / ((n + 1)^2 + k^2) at normal incidence
+        let f = fresnel_conductor(1.0, 0.2, 3.9);
+        assert!((f - (0.64 + 15.21) / (1.44 + 15.21)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_transmittance() {
+        let t = transmittance(Vec3(0.5, 0.0, 2.0), 2.0);
+        assert!((t - Vec3((-1.0f32).exp(), 1.0, (-4.0f32).exp())).len() < 1e-6);
+        // channels that are not absorbed pass an infinite distance
+        assert_eq!(
+            transmittance(Vec3(0.5, 0.0, 0.0), f32::INFINITY),
+            Vec3(0.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_heuristics() {
+        for &(f, g) in &[(1.0, 1.0), (0.5, 3.0), (2.0, 0.0)] {
+            assert!((balance_heuristic(f, g) + balance_heuristic(g, f) - 1.0).abs() < 1e-6);
+            assert!((power_heuristic(f, g) + power_heuristic(g, f) - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(balance_heuristic(0.5, 1.5), 0.25);
+        assert_eq!(power_heuristic(1.0, 3.0), 0.1);
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+    }
+}
@@ -40,6 +40,16 @@ impl AABB {
         self.max
     }
 
+    /// Returns the surface area of the box, which is 0 for hidden boxes.
+    pub fn surface_area(&self) -> f32 {
+        if self.hidden {
+            return 0.0;
+        }
+
+        let d = self.max - self.min;
+        2.0 * (d.0 * d.1 + d.1 * d.2 + d.2 * d.0)
+    }
+
     pub fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> bool {
         if self.hidden {
             return false;
@@ -48,21 +58,21 @@ impl AABB {
         let (t0, t1) = hit_axis(self.min.0, self.max.0, r.origin().0, r.direction().0);
         let t_min = t_min.max(t0);
         let t_max = t_max.min(t1);
-        if t_min < t_max {
+        if t_max <= t_min {
             return false;
         }
 
         let (t0, t1) = hit_axis(self.min.1, self.max.1, r.origin().1, r.direction().1);
         let t_min = t_min.max(t0);
         let t_max = t_max.min(t1);
-        if t_min < t_max {
+        if t_max <= t_min {
             return false;
         }
 
         let (t0, t1) = hit_axis(self.min.2, self.max.2, r.origin().2, r.direction().2);
         let t_min = t_min.max(t0);
         let t_max = t_max.min(t1);
-        t_min > t_max
+        t_min < t_max
     }
 }
 
@@ -98,3 +108,30 @@ pub fn surrounding_box(box0: AABB, box1: AABB) -> AABB {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aabb_hit() {
+        let bbox = AABB::new(Vec3(-1.0, -1.0, -1.0), Vec3(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3(0.0, 0.0, -5.0), Vec3(0.0, 0.0, 1.0), 0.0);
+        assert!(bbox.hit(&ray, 0.001, f32::MAX));
+        assert!(!bbox.hit(&ray, 0.001, 3.0));
+
+        let ray = Ray::new(Vec3(0.5, 0.5, -5.0), Vec3(0.1, 0.1, 1.0), 0.0);
+        assert!(bbox.hit(&ray, 0.001, f32::MAX));
+    }
+
+    #[test]
+    fn test_aabb_miss() {
+        let bbox = AABB::new(Vec3(-1.0, -1.0, -1.0), Vec3(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3(5.0, 5.0, -5.0), Vec3(0.0, 0.0, 1.0), 0.0);
+        assert!(!bbox.hit(&ray, 0.001, f32::MAX));
+
+        let ray = Ray::new(Vec3(0.0, 0.0, -5.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert!(!bbox.hit(&ray, 0.001, f32::MAX));
+        assert!(!AABB::new_hidden().hit(&ray, f32::MIN, f32::MAX));
+    }
+}
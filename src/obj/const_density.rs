@@ -21,6 +21,7 @@ impl<H: Hittable, S: Scatterable> ConstDensity<H, S> {
 }
 
 impl<H: Hittable, S: Scatterable> Hittable for ConstDensity<H, S> {
+    #[allow(mismatched_lifetime_syntaxes, clippy::legacy_numeric_constants)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         if let Some(hit1) = self.hittable.hit(r, std::f32::MIN, std::f32::MAX) {
             if let Some(hit2) = self.hittable.hit(r, hit1.t() + 0.0001, std::f32::MAX) {
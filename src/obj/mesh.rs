@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use crate::math::Vec3;
+use crate::mtl::Scatterable;
+use crate::obj::triangle::{
+    intersect, triangle_bbox, triangle_pdf, triangle_power, triangle_random,
+};
+use crate::obj::AABB;
+use crate::trace::{Hit, Hittable, Ray};
+
+/// TriangleMesh holds the vertex buffers of a mesh once and describes its
+/// triangles as indices into them. It is turned into hittable triangles with
+/// TriangleMesh::triangles, which share the buffers and the material.
+///     positions - vertex positions
+///     normals - vertex normals for smooth shading; either empty or with the
+///               same length as positions
+///     uvs - vertex texture coordinates; either empty or with the same length
+///           as positions
+///     indices - vertex indices for each triangle in counter-clockwise order
+pub struct TriangleMesh<M: Scatterable> {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f32, f32)>,
+    indices: Vec<[u32; 3]>,
+    mat: M,
+}
+
+impl<M: Scatterable> TriangleMesh<M> {
+    /// Returns a new mesh for the given buffers.
+    /// Panics if the buffer lengths do not match or an index is out of range.
+    pub fn new(
+        positions: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f32, f32)>,
+        indices: Vec<[u32; 3]>,
+        mat: M,
+    ) -> TriangleMesh<M> {
+        assert!(normals.is_empty() || normals.len() == positions.len());
+        assert!(uvs.is_empty() || uvs.len() == positions.len());
+        assert!(indices
+            .iter()
+            .all(|tri| tri.iter().all(|&i| (i as usize) < positions.len())));
+
+        TriangleMesh {
+            positions,
+            normals,
+            uvs,
+            indices,
+            mat,
+        }
+    }
+
+    /// Returns the number of triangles in the mesh.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns whether the mesh has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the positions of the vertices of the triangle idx, in the order
+    /// of its indices.
+    pub fn positions(&self, idx: usize) -> [Vec3; 3] {
+        let [i0, i1, i2] = self.vertices(idx);
+        [self.positions[i0], self.positions[i1], self.positions[i2]]
+    }
+
+    /// Consumes the mesh and returns its triangles, e.g. for building a BVHNode.
+    pub fn triangles(self) -> Vec<MeshTriangle<M>> {
+        let mesh = Arc::new(self);
+        (0..mesh.len())
+            .map(|idx| MeshTriangle {
+                mesh: Arc::clone(&mesh),
+                idx,
+            })
+            .collect()
+    }
+
+    fn vertices(&self, idx: usize) -> [usize; 3] {
+        let [i0, i1, i2] = self.indices[idx];
+        [i0 as usize, i1 as usize, i2 as usize]
+    }
+}
+
+/// MeshTriangle is a single triangle of a TriangleMesh.
+pub struct MeshTriangle<M: Scatterable> {
+    mesh: Arc<TriangleMesh<M>>,
+    idx: usize,
+}
+
+impl<M: Scatterable> Hittable for MeshTriangle<M> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let mesh = self.mesh.as_ref();
+        let [i0, i1, i2] = mesh.vertices(self.idx);
+        let (v0, v1, v2) = (mesh.positions[i0], mesh.positions[i1], mesh.positions[i2]);
+
+        let (t, b1, b2) = intersect(r, v0, v1, v2)?;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let b0 = 1.0 - b1 - b2;
+        let (u, v) = if mesh.uvs.is_empty() {
+            (b1, b2)
+        } else {
+            let (uv0, uv1, uv2) = (mesh.uvs[i0], mesh.uvs[i1], mesh.uvs[i2]);
+            (
+                b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0,
+                b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1,
+            )
+        };
+
+        let n = (v1 - v0).cross(v2 - v0).as_unit();
+        let mut hit = Hit::new(t, r.point_at_param(t), n, &mesh.mat, u, v);
+        if !mesh.normals.is_empty() {
+            let n = b0 * mesh.normals[i0] + b1 * mesh.normals[i1] + b2 * mesh.normals[i2];
+            hit.set_shading_normal(n.as_unit());
+        }
+        Some(hit)
+    }
+
+    fn bounding_box(&self, _: f32, _: f32) -> AABB {
+        let mesh = self.mesh.as_ref();
+        let [i0, i1, i2] = mesh.vertices(self.idx);
+        triangle_bbox(mesh.positions[i0], mesh.positions[i1], mesh.positions[i2])
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mesh.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        let mesh = self.mesh.as_ref();
+        let [i0, i1, i2] = mesh.vertices(self.idx);
+        triangle_random(mesh.positions[i0], mesh.positions[i1], mesh.positions[i2]) - origin
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        let mesh = self.mesh.as_ref();
+        let [i0, i1, i2] = mesh.vertices(self.idx);
+        triangle_pdf(r, mesh.positions[i0], mesh.positions[i1], mesh.positions[i2])
+    }
+
+    fn power(&self) -> f32 {
+        let mesh = self.mesh.as_ref();
+        let [i0, i1, i2] = mesh.vertices(self.idx);
+        let (v0, v1, v2) = (mesh.positions[i0], mesh.positions[i1], mesh.positions[i2]);
+        triangle_power(&mesh.mat, v0, v1, v2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::ScatterRecord;
+    use crate::trace::BVHNode;
+
+    fn pyramid() -> TriangleMesh<TestMaterial> {
+        let positions = vec![
+            Vec3(-1.0, 0.0, -1.0),
+            Vec3(1.0, 0.0, -1.0),
+            Vec3(1.0, 0.0, 1.0),
+            Vec3(-1.0, 0.0, 1.0),
+            Vec3(0.0, 1.0, 0.0),
+        ];
+        let normals = positions.iter().map(|p| p.as_unit()).collect();
+        let uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.5, 0.5)];
+        let indices = vec![[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]];
+        TriangleMesh::new(positions, normals, uvs, indices, TestMaterial {})
+    }
+
+    #[test]
+    fn test_mesh_triangles_bbox() {
+        let triangles = pyramid().triangles();
+        assert_eq!(triangles.len(), 4);
+
+        let bbox = triangles[1].bounding_box(0.0, 0.0);
+        assert!((bbox.min() - Vec3(0.0, 0.0, -1.0)).len() < 0.001);
+        assert!((bbox.max() - Vec3(1.0, 1.0, 1.0)).len() < 0.001);
+    }
+
+    #[test]
+    fn test_mesh_hit_interpolation() {
+        let triangles = pyramid()
+            .triangles()
+            .into_iter()
+            .map(|tri| Arc::new(tri) as Arc<dyn Hittable>)
+            .collect();
+        let bvh = BVHNode::new(triangles, 0.0, 1.0);
+
+        // straight down onto the apex region of the +x face
+        let ray = Ray::new(Vec3(0.5, 2.0, 0.0), Vec3(0.0, -1.0, 0.0), 0.0);
+        let hit = bvh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert!((hit.t() - 1.5).abs() < 1e-5);
+
+        // halfway between the +x edge midpoint and the apex: b1 = b2 = 0.25, b4 = 0.5
+        let expected_n = (0.25 * Vec3(1.0, 0.0, -1.0).as_unit()
+            + 0.25 * Vec3(1.0, 0.0, 1.0).as_unit()
+            + 0.5 * Vec3(0.0, 1.0, 0.0))
+        .as_unit();
+        assert!((hit.n() - expected_n).len() < 1e-5);
+        // the face winds inwards, its geometric normal is turned to the outside
+        assert!((hit.ng() - Vec3(1.0, 1.0, 0.0).as_unit()).len() < 1e-5);
+        assert!((hit.u() - 0.75).abs() < 1e-5);
+        assert!((hit.v() - 0.5).abs() < 1e-5);
+    }
+
+    struct TestMaterial {}
+
+    impl Scatterable for TestMaterial {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
+            None
+        }
+    }
+}
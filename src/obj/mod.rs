@@ -1,16 +1,21 @@
 pub use aabb::surrounding_box;
 pub use aabb::AABB;
 pub use const_density::ConstDensity;
+pub use mesh::{MeshTriangle, TriangleMesh};
 pub use moving_sphere::MovSphere;
 pub use rect::{XYRect, XZRect, YZRect};
 pub use rect_box::RectBox;
 pub use sphere::Sphere;
+pub use triangle::Triangle;
 
 mod aabb;
 mod const_density;
+mod mesh;
 mod moving_sphere;
 mod rect;
 mod rect_box;
 mod sphere;
+mod triangle;
+mod utils;
 
 pub mod transform;
@@ -1,5 +1,6 @@
 use crate::math::Vec3;
 use crate::mtl::Scatterable;
+use crate::obj::utils::{emitted_power, sphere_pdf, sphere_random};
 use crate::obj::{surrounding_box, Sphere, AABB};
 use crate::trace::{Hit, Hittable, Ray};
 
@@ -39,6 +40,7 @@ impl<M: Scatterable> MovSphere<M> {
 }
 
 impl<M: Scatterable> Hittable for MovSphere<M> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let oc = r.origin() - self.center(r.time());
         let a = r.direction().dot(r.direction());
@@ -92,4 +94,25 @@ impl<M: Scatterable> Hittable for MovSphere<M> {
 
         surrounding_box(box_begin, box_end)
     }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, time: f32) -> Vec3 {
+        sphere_random(self.center(time), self.r, origin)
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        match self.hit(r, 0.001, f32::MAX) {
+            Some(_) => sphere_pdf(self.center(r.time()), self.r, r.origin()),
+            None => 0.0,
+        }
+    }
+
+    fn power(&self) -> f32 {
+        let area = 4.0 * std::f32::consts::PI * self.r * self.r;
+        let top = self.center_begin + Vec3(0.0, self.r, 0.0);
+        emitted_power(&self.mat, area, top, Vec3(0.0, 1.0, 0.0))
+    }
 }
@@ -1,7 +1,9 @@
 use crate::math::Vec3;
 use crate::mtl::Scatterable;
+use crate::obj::utils::{area_pdf, emitted_power};
 use crate::obj::AABB;
 use crate::trace::{Hit, Hittable, Ray};
+use crate::utils::rng::uniform_in_range;
 
 const EPS: f32 = 0.0001;
 
@@ -31,6 +33,7 @@ impl<M: Scatterable> XYRect<M> {
 }
 
 impl<M: Scatterable> Hittable for XYRect<M> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let t = (self.k - r.origin().2) / r.direction().2;
         if t < t_min || t > t_max {
@@ -59,6 +62,32 @@ impl<M: Scatterable> Hittable for XYRect<M> {
             Vec3(self.x1, self.y1, self.k + EPS),
         )
     }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        let x = uniform_in_range(self.x0, self.x1);
+        let y = uniform_in_range(self.y0, self.y1);
+        Vec3(x, y, self.k) - origin
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        match self.hit(r, 0.001, f32::MAX) {
+            Some(hit) => {
+                let area = (self.x1 - self.x0) * (self.y1 - self.y0);
+                area_pdf(r, hit.t(), Vec3(0.0, 0.0, 1.0), area)
+            }
+            None => 0.0,
+        }
+    }
+
+    fn power(&self) -> f32 {
+        let area = (self.x1 - self.x0) * (self.y1 - self.y0);
+        let center = Vec3((self.x0 + self.x1) / 2.0, (self.y0 + self.y1) / 2.0, self.k);
+        emitted_power(&self.mat, area, center, Vec3(0.0, 0.0, 1.0))
+    }
 }
 
 pub struct XZRect<M: Scatterable> {
@@ -80,6 +109,7 @@ impl<M: Scatterable> XZRect<M> {
 }
 
 impl<M: Scatterable> Hittable for XZRect<M> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let t = (self.k - r.origin().1) / r.direction().1;
         if t < t_min || t > t_max {
@@ -108,6 +138,32 @@ impl<M: Scatterable> Hittable for XZRect<M> {
             Vec3(self.x1, self.k + EPS, self.z1),
         )
     }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        let x = uniform_in_range(self.x0, self.x1);
+        let z = uniform_in_range(self.z0, self.z1);
+        Vec3(x, self.k, z) - origin
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        match self.hit(r, 0.001, f32::MAX) {
+            Some(hit) => {
+                let area = (self.x1 - self.x0) * (self.z1 - self.z0);
+                area_pdf(r, hit.t(), Vec3(0.0, 1.0, 0.0), area)
+            }
+            None => 0.0,
+        }
+    }
+
+    fn power(&self) -> f32 {
+        let area = (self.x1 - self.x0) * (self.z1 - self.z0);
+        let center = Vec3((self.x0 + self.x1) / 2.0, self.k, (self.z0 + self.z1) / 2.0);
+        emitted_power(&self.mat, area, center, Vec3(0.0, 1.0, 0.0))
+    }
 }
 
 pub struct YZRect<M: Scatterable> {
@@ -136,6 +192,7 @@ impl<M: Scatterable> YZRect<M> {
 }
 
 impl<M: Scatterable> Hittable for YZRect<M> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let t = (self.k - r.origin().0) / r.direction().0;
         if t < t_min || t > t_max {
@@ -164,13 +221,41 @@ impl<M: Scatterable> Hittable for YZRect<M> {
             Vec3(self.k + EPS, self.y1, self.z1),
         )
     }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        let y = uniform_in_range(self.y0, self.y1);
+        let z = uniform_in_range(self.z0, self.z1);
+        Vec3(self.k, y, z) - origin
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        match self.hit(r, 0.001, f32::MAX) {
+            Some(hit) => {
+                let area = (self.y1 - self.y0) * (self.z1 - self.z0);
+                area_pdf(r, hit.t(), Vec3(1.0, 0.0, 0.0), area)
+            }
+            None => 0.0,
+        }
+    }
+
+    fn power(&self) -> f32 {
+        let area = (self.y1 - self.y0) * (self.z1 - self.z0);
+        let center = Vec3(self.k, (self.y0 + self.y1) / 2.0, (self.z0 + self.z1) / 2.0);
+        emitted_power(&self.mat, area, center, Vec3(1.0, 0.0, 0.0))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mtl::ScatterRecord;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_xy_rect_hit() {
         let rect = XYRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0), 0.0);
@@ -179,6 +264,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_xz_rect_hit() {
         let rect = XZRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0), 0.0);
@@ -187,6 +273,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_yz_rect_hit() {
         let rect = YZRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0), 0.0);
@@ -195,6 +282,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_xy_rect_miss() {
         let rect = XYRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(-1.0, -1.0, -1.0), 0.0);
@@ -203,6 +291,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_xz_rect_miss() {
         let rect = XZRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(-1.0, -1.0, -1.0), 0.0);
@@ -211,6 +300,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_yz_rect_miss() {
         let rect = YZRect::new(-1.0, -1.0, 1.0, 1.0, 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(-1.0, -1.0, -1.0), 0.0);
@@ -223,9 +313,9 @@ mod tests {
     }
 
     impl Scatterable for TestMaterial {
-        fn scatter(&self, _: &Ray, _: Hit) -> Option<(Ray, Vec3)> {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
             let rref = self.res.as_ref();
-            Some((rref?.0.clone(), rref?.1))
+            Some(ScatterRecord::specular(rref?.0.clone(), rref?.1))
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
 use crate::mtl::Scatterable;
 use crate::obj::transform::FlipNormals;
@@ -12,9 +14,10 @@ pub struct RectBox {
 }
 
 impl RectBox {
+    #[allow(clippy::vec_init_then_push)]
     pub fn new<M: Scatterable + Clone + 'static>(p_min: Vec3, p_max: Vec3, mat: M) -> RectBox {
-        let mut sides: Vec<Box<dyn Hittable>> = Vec::with_capacity(6);
-        sides.push(Box::new(XYRect::new(
+        let mut sides: Vec<Arc<dyn Hittable>> = Vec::with_capacity(6);
+        sides.push(Arc::new(XYRect::new(
             p_min.0,
             p_min.1,
             p_max.0,
@@ -22,7 +25,7 @@ impl RectBox {
             p_max.2,
             mat.clone(),
         )));
-        sides.push(Box::new(FlipNormals::new(XYRect::new(
+        sides.push(Arc::new(FlipNormals::new(XYRect::new(
             p_min.0,
             p_min.1,
             p_max.0,
@@ -30,7 +33,7 @@ impl RectBox {
             p_min.2,
             mat.clone(),
         ))));
-        sides.push(Box::new(XZRect::new(
+        sides.push(Arc::new(XZRect::new(
             p_min.0,
             p_min.2,
             p_max.0,
@@ -38,7 +41,7 @@ impl RectBox {
             p_max.1,
             mat.clone(),
         )));
-        sides.push(Box::new(FlipNormals::new(XZRect::new(
+        sides.push(Arc::new(FlipNormals::new(XZRect::new(
             p_min.0,
             p_min.2,
             p_max.0,
@@ -46,7 +49,7 @@ impl RectBox {
             p_min.1,
             mat.clone(),
         ))));
-        sides.push(Box::new(YZRect::new(
+        sides.push(Arc::new(YZRect::new(
             p_min.1,
             p_min.2,
             p_max.1,
@@ -54,7 +57,7 @@ impl RectBox {
             p_max.0,
             mat.clone(),
         )));
-        sides.push(Box::new(FlipNormals::new(YZRect::new(
+        sides.push(Arc::new(FlipNormals::new(YZRect::new(
             p_min.1,
             p_min.2,
             p_max.1,
@@ -72,6 +75,7 @@ impl RectBox {
 }
 
 impl Hittable for RectBox {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         self.sides.hit(r, t_min, t_max)
     }
@@ -79,4 +83,8 @@ impl Hittable for RectBox {
     fn bounding_box(&self, _: f32, _: f32) -> AABB {
         AABB::new(self.p_min, self.p_max)
     }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        self.sides.lights(lights);
+    }
 }
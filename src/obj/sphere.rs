@@ -1,5 +1,6 @@
 use crate::math::Vec3;
 use crate::mtl::Scatterable;
+use crate::obj::utils::{emitted_power, sphere_pdf, sphere_random};
 use crate::obj::AABB;
 use crate::trace::{Hit, Hittable, Ray};
 
@@ -25,6 +26,7 @@ impl<M: Scatterable> Sphere<M> {
 }
 
 impl<M: Scatterable> Hittable for Sphere<M> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let oc = r.origin() - self.center;
         let a = r.direction().dot(r.direction());
@@ -72,13 +74,36 @@ impl<M: Scatterable> Hittable for Sphere<M> {
             self.center + Vec3(self.r, self.r, self.r),
         )
     }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        sphere_random(self.center, self.r, origin)
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        match self.hit(r, 0.001, f32::MAX) {
+            Some(_) => sphere_pdf(self.center, self.r, r.origin()),
+            None => 0.0,
+        }
+    }
+
+    fn power(&self) -> f32 {
+        let area = 4.0 * std::f32::consts::PI * self.r * self.r;
+        let top = self.center + Vec3(0.0, self.r, 0.0);
+        emitted_power(&self.mat, area, top, Vec3(0.0, 1.0, 0.0))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mtl::ScatterRecord;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_sphere_hit() {
         let s = Sphere::new(Vec3(1.0, 1.0, 1.0), 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0), 0.0);
@@ -87,6 +112,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_sphere_miss() {
         let s = Sphere::new(Vec3(1.0, 1.0, 1.0), 1.0, TestMaterial { res: None });
         let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(-1.0, -1.0, -1.0), 0.0);
@@ -108,9 +134,9 @@ mod tests {
     }
 
     impl Scatterable for TestMaterial {
-        fn scatter(&self, _: &Ray, _: Hit) -> Option<(Ray, Vec3)> {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
             let rref = self.res.as_ref();
-            Some((rref?.0.clone(), rref?.1))
+            Some(ScatterRecord::specular(rref?.0.clone(), rref?.1))
         }
     }
 }
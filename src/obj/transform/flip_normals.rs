@@ -1,3 +1,6 @@
+use std::sync::Arc;
+
+use crate::math::Vec3;
 use crate::obj::AABB;
 use crate::trace::{Hit, Hittable, Ray};
 
@@ -12,16 +15,13 @@ impl<H: Hittable> FlipNormals<H> {
 }
 
 impl<H: Hittable> Hittable for FlipNormals<H> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         if let Some(hit) = self.hittable.hit(r, t_min, t_max) {
-            return Some(Hit::new(
-                hit.t(),
-                hit.p(),
-                -hit.n(),
-                hit.mat_ref(),
-                hit.u(),
-                hit.v(),
-            ));
+            let mut flipped =
+                Hit::new(hit.t(), hit.p(), -hit.ng(), hit.mat_ref(), hit.u(), hit.v());
+            flipped.set_shading_normal(-hit.n());
+            return Some(flipped);
         }
 
         None
@@ -30,4 +30,28 @@ impl<H: Hittable> Hittable for FlipNormals<H> {
     fn bounding_box(&self, t_min: f32, t_max: f32) -> AABB {
         self.hittable.bounding_box(t_min, t_max)
     }
+
+    fn is_emissive(&self) -> bool {
+        self.hittable.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, time: f32) -> Vec3 {
+        self.hittable.random(origin, time)
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        self.hittable.pdf_value(r)
+    }
+
+    fn power(&self) -> f32 {
+        self.hittable.power()
+    }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        let mut inner = Vec::new();
+        self.hittable.lights(&mut inner);
+        for light in inner {
+            lights.push(Arc::new(FlipNormals::new(light)));
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
 use crate::obj::AABB;
 use crate::trace::{Hit, Hittable, Ray};
@@ -18,6 +20,7 @@ impl<H: Hittable> RotateY<H> {
         RotateY::rotate_bbox(hittable, cos_theta, sin_theta, bbox)
     }
 
+    #[allow(clippy::legacy_numeric_constants)]
     fn rotate_bbox(hittable: H, cos_theta: f32, sin_theta: f32, bbox: AABB) -> RotateY<H> {
         let mut min = Vec3(std::f32::MAX, std::f32::MAX, std::f32::MAX);
         let mut max = Vec3(std::f32::MIN, std::f32::MIN, std::f32::MIN);
@@ -54,9 +57,28 @@ impl<H: Hittable> RotateY<H> {
             bbox: AABB::new(min, max),
         }
     }
+
+    /// Returns the vector v rotated from world space into the object's space.
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        Vec3(
+            self.cos_theta * v.0 - self.sin_theta * v.2,
+            v.1,
+            self.sin_theta * v.0 + self.cos_theta * v.2,
+        )
+    }
+
+    /// Returns the vector v rotated from the object's space into world space.
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        Vec3(
+            self.cos_theta * v.0 + self.sin_theta * v.2,
+            v.1,
+            -self.sin_theta * v.0 + self.cos_theta * v.2,
+        )
+    }
 }
 
 impl<H: Hittable> Hittable for RotateY<H> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let mut origin = r.origin();
         origin.0 = self.cos_theta * r.origin().0 - self.sin_theta * r.origin().2;
@@ -76,7 +98,13 @@ impl<H: Hittable> Hittable for RotateY<H> {
             n.0 = self.cos_theta * hit.n().0 + self.sin_theta * hit.n().2;
             n.2 = -self.sin_theta * hit.n().0 + self.cos_theta * hit.n().2;
 
-            return Some(Hit::new(hit.t(), p, n, hit.mat_ref(), hit.u(), hit.v()));
+            let mut ng = hit.ng();
+            ng.0 = self.cos_theta * hit.ng().0 + self.sin_theta * hit.ng().2;
+            ng.2 = -self.sin_theta * hit.ng().0 + self.cos_theta * hit.ng().2;
+
+            let mut rotated = Hit::new(hit.t(), p, ng, hit.mat_ref(), hit.u(), hit.v());
+            rotated.set_shading_normal(n);
+            return Some(rotated);
         }
 
         None
@@ -85,4 +113,39 @@ impl<H: Hittable> Hittable for RotateY<H> {
     fn bounding_box(&self, _: f32, _: f32) -> AABB {
         self.bbox.clone()
     }
+
+    fn is_emissive(&self) -> bool {
+        self.hittable.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, time: f32) -> Vec3 {
+        self.to_world(self.hittable.random(self.to_object(origin), time))
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        let rotated_ray = Ray::new(
+            self.to_object(r.origin()),
+            self.to_object(r.direction()),
+            r.time(),
+        );
+        self.hittable.pdf_value(&rotated_ray)
+    }
+
+    fn power(&self) -> f32 {
+        self.hittable.power()
+    }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        let mut inner = Vec::new();
+        self.hittable.lights(&mut inner);
+        for light in inner {
+            let bbox = light.bounding_box(0.0, 1.0);
+            lights.push(Arc::new(RotateY::rotate_bbox(
+                light,
+                self.cos_theta,
+                self.sin_theta,
+                bbox,
+            )));
+        }
+    }
 }
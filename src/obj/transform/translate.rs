@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
 use crate::obj::AABB;
 use crate::trace::{Hit, Hittable, Ray};
@@ -14,17 +16,20 @@ impl<H: Hittable> Translate<H> {
 }
 
 impl<H: Hittable> Hittable for Translate<H> {
+    #[allow(mismatched_lifetime_syntaxes)]
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
         let moved_ray = Ray::new(r.origin() - self.offset, r.direction(), r.time());
         if let Some(hit) = self.hittable.hit(&moved_ray, t_min, t_max) {
-            return Some(Hit::new(
+            let mut moved = Hit::new(
                 hit.t(),
                 hit.p() + self.offset,
-                hit.n(),
+                hit.ng(),
                 hit.mat_ref(),
                 hit.u(),
                 hit.v(),
-            ));
+            );
+            moved.set_shading_normal(hit.n());
+            return Some(moved);
         }
 
         None
@@ -37,4 +42,29 @@ impl<H: Hittable> Hittable for Translate<H> {
             bbox.max() + self.offset,
         )
     }
+
+    fn is_emissive(&self) -> bool {
+        self.hittable.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, time: f32) -> Vec3 {
+        self.hittable.random(origin - self.offset, time)
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        let moved_ray = Ray::new(r.origin() - self.offset, r.direction(), r.time());
+        self.hittable.pdf_value(&moved_ray)
+    }
+
+    fn power(&self) -> f32 {
+        self.hittable.power()
+    }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        let mut inner = Vec::new();
+        self.hittable.lights(&mut inner);
+        for light in inner {
+            lights.push(Arc::new(Translate::new(light, self.offset)));
+        }
+    }
 }
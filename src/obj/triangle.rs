@@ -0,0 +1,246 @@
+use crate::math::Vec3;
+use crate::mtl::Scatterable;
+use crate::obj::utils::{area_pdf, emitted_power};
+use crate::obj::AABB;
+use crate::trace::{Hit, Hittable, Ray};
+use crate::utils::rng::uniform_in_range;
+
+const EPS: f32 = 0.0001;
+
+/// Triangle is a flat triangle given by its three vertices in counter-clockwise
+/// order. Vertices may carry normals, which are interpolated for smooth
+/// shading, and texture coordinates, which are interpolated into hit.u and hit.v.
+pub struct Triangle<M: Scatterable> {
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: [(f32, f32); 3],
+    mat: M,
+}
+
+impl<M: Scatterable> Triangle<M> {
+    /// Returns a new triangle with the geometric normal and texture
+    /// coordinates (0, 0), (1, 0) and (0, 1) for the vertices.
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, mat: M) -> Triangle<M> {
+        Triangle::with_attributes(
+            [v0, v1, v2],
+            None,
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            mat,
+        )
+    }
+
+    /// Returns a new triangle with the given per-vertex normals and texture
+    /// coordinates. The geometric normal is used when normals are None.
+    pub fn with_attributes(
+        vertices: [Vec3; 3],
+        normals: Option<[Vec3; 3]>,
+        uvs: [(f32, f32); 3],
+        mat: M,
+    ) -> Triangle<M> {
+        Triangle {
+            vertices,
+            normals,
+            uvs,
+            mat,
+        }
+    }
+}
+
+impl<M: Scatterable> Hittable for Triangle<M> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let [v0, v1, v2] = self.vertices;
+        let (t, b1, b2) = intersect(r, v0, v1, v2)?;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let b0 = 1.0 - b1 - b2;
+        let [uv0, uv1, uv2] = self.uvs;
+        let mut hit = Hit::new(
+            t,
+            r.point_at_param(t),
+            (v1 - v0).cross(v2 - v0).as_unit(),
+            &self.mat,
+            b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0,
+            b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1,
+        );
+        if let Some([n0, n1, n2]) = self.normals {
+            hit.set_shading_normal((b0 * n0 + b1 * n1 + b2 * n2).as_unit());
+        }
+        Some(hit)
+    }
+
+    fn bounding_box(&self, _: f32, _: f32) -> AABB {
+        triangle_bbox(self.vertices[0], self.vertices[1], self.vertices[2])
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, _: f32) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        triangle_random(v0, v1, v2) - origin
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        let [v0, v1, v2] = self.vertices;
+        triangle_pdf(r, v0, v1, v2)
+    }
+
+    fn power(&self) -> f32 {
+        let [v0, v1, v2] = self.vertices;
+        triangle_power(&self.mat, v0, v1, v2)
+    }
+}
+
+/// Returns (t, b1, b2) where the ray hits the triangle (v0, v1, v2), b1 and b2
+/// being the barycentric coordinates of the hit for v1 and v2.
+/// It uses the Möller–Trumbore algorithm.
+pub(crate) fn intersect(r: &Ray, v0: Vec3, v1: Vec3, v2: Vec3) -> Option<(f32, f32, f32)> {
+    let e1 = v1 - v0;
+    let e2 = v2 - v0;
+    let pvec = r.direction().cross(e2);
+    let det = e1.dot(pvec);
+    if det.abs() < 1e-12 {
+        // the ray is parallel to the triangle
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let tvec = r.origin() - v0;
+    let b1 = tvec.dot(pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let qvec = tvec.cross(e1);
+    let b2 = r.direction().dot(qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    Some((e2.dot(qvec) * inv_det, b1, b2))
+}
+
+/// Returns a point of the triangle (v0, v1, v2), uniformly distributed over
+/// its area.
+pub(crate) fn triangle_random(v0: Vec3, v1: Vec3, v2: Vec3) -> Vec3 {
+    let su = uniform_in_range(0.0f32, 1.0).sqrt();
+    let r2 = uniform_in_range(0.0, 1.0);
+    (1.0 - su) * v0 + su * (1.0 - r2) * v1 + su * r2 * v2
+}
+
+/// Returns the density with which a direction from the ray origin towards
+/// triangle_random of the triangle (v0, v1, v2) is the direction of the ray.
+pub(crate) fn triangle_pdf(r: &Ray, v0: Vec3, v1: Vec3, v2: Vec3) -> f32 {
+    match intersect(r, v0, v1, v2) {
+        Some((t, _, _)) if t > 0.001 => {
+            let cross = (v1 - v0).cross(v2 - v0);
+            area_pdf(r, t, cross.as_unit(), 0.5 * cross.len())
+        }
+        _ => 0.0,
+    }
+}
+
+/// Returns an estimate of the power emitted by the triangle (v0, v1, v2) with
+/// the given material.
+pub(crate) fn triangle_power(mat: &dyn Scatterable, v0: Vec3, v1: Vec3, v2: Vec3) -> f32 {
+    let cross = (v1 - v0).cross(v2 - v0);
+    let area = 0.5 * cross.len();
+    if area <= 0.0 {
+        return 0.0;
+    }
+    emitted_power(mat, area, (v0 + v1 + v2) / 3.0, cross / (2.0 * area))
+}
+
+/// Returns the bounding box of the triangle (v0, v1, v2). The box is padded,
+/// so that it has volume for axis-aligned triangles.
+pub(crate) fn triangle_bbox(v0: Vec3, v1: Vec3, v2: Vec3) -> AABB {
+    AABB::new(
+        Vec3(
+            v0.0.min(v1.0).min(v2.0) - EPS,
+            v0.1.min(v1.1).min(v2.1) - EPS,
+            v0.2.min(v1.2).min(v2.2) - EPS,
+        ),
+        Vec3(
+            v0.0.max(v1.0).max(v2.0) + EPS,
+            v0.1.max(v1.1).max(v2.1) + EPS,
+            v0.2.max(v1.2).max(v2.2) + EPS,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::ScatterRecord;
+
+    #[test]
+    fn test_triangle_hit() {
+        let tri = Triangle::new(
+            Vec3(0.0, 0.0, -1.0),
+            Vec3(1.0, 0.0, -1.0),
+            Vec3(0.0, 1.0, -1.0),
+            TestMaterial {},
+        );
+        let ray = Ray::new(Vec3(0.25, 0.5, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = tri.hit(&ray, 0.0, 10.0).unwrap();
+        assert_eq!(hit.t(), 1.0);
+        assert_eq!(hit.n(), Vec3(0.0, 0.0, 1.0));
+        assert_eq!((hit.u(), hit.v()), (0.25, 0.5));
+    }
+
+    #[test]
+    fn test_triangle_miss() {
+        let tri = Triangle::new(
+            Vec3(0.0, 0.0, -1.0),
+            Vec3(1.0, 0.0, -1.0),
+            Vec3(0.0, 1.0, -1.0),
+            TestMaterial {},
+        );
+        let ray = Ray::new(Vec3(0.75, 0.75, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert!(tri.hit(&ray, 0.0, 10.0).is_none());
+
+        let ray = Ray::new(Vec3(0.25, 0.25, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert!(tri.hit(&ray, 0.0, 0.5).is_none());
+
+        let ray = Ray::new(Vec3(0.25, 0.25, 0.0), Vec3(1.0, 0.0, 0.0), 0.0);
+        assert!(tri.hit(&ray, 0.0, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_triangle_attributes() {
+        let tri = Triangle::with_attributes(
+            [
+                Vec3(0.0, 0.0, 0.0),
+                Vec3(2.0, 0.0, 0.0),
+                Vec3(0.0, 2.0, 0.0),
+            ],
+            Some([
+                Vec3(0.0, 0.0, 1.0),
+                Vec3(1.0, 0.0, 0.0),
+                Vec3(0.0, 0.0, 1.0),
+            ]),
+            [(0.5, 0.5), (1.0, 0.5), (0.5, 1.0)],
+            TestMaterial {},
+        );
+        let ray = Ray::new(Vec3(1.0, 0.0, 1.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = tri.hit(&ray, 0.0, 10.0).unwrap();
+        assert_eq!(hit.n(), Vec3(0.5, 0.0, 0.5).as_unit());
+        assert_eq!(hit.ng(), Vec3(0.0, 0.0, 1.0));
+        assert_eq!((hit.u(), hit.v()), (0.75, 0.5));
+
+        let bbox = tri.bounding_box(0.0, 0.0);
+        assert_eq!(bbox.min(), Vec3(-EPS, -EPS, -EPS));
+        assert_eq!(bbox.max(), Vec3(2.0 + EPS, 2.0 + EPS, EPS));
+    }
+
+    struct TestMaterial {}
+
+    impl Scatterable for TestMaterial {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
+            None
+        }
+    }
+}
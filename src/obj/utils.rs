@@ -0,0 +1,56 @@
+use std::f32::consts::PI;
+
+use crate::math::{Vec3, ONB};
+use crate::mtl::Scatterable;
+use crate::trace::{Hit, Ray};
+use crate::utils::rng::{rand_to_sphere, rand_unit_vector};
+
+/// Returns the solid angle density of a point that is sampled uniformly over
+/// a surface with the given area, for the ray that hits the surface at t with
+/// the normal n.
+pub fn area_pdf(r: &Ray, t: f32, n: Vec3, area: f32) -> f32 {
+    let dir = r.direction();
+    let cos = (dir.dot(n) / dir.len()).abs();
+    if cos < 1e-6 || area <= 0.0 {
+        return 0.0;
+    }
+
+    t * t * dir.sq_len() / (cos * area)
+}
+
+/// Returns an estimate of the power emitted by a surface with the given area,
+/// from the radiance that the material emits to both sides at its point p with
+/// the normal n and the texture coordinates (0.5, 0.5).
+pub fn emitted_power(mat: &dyn Scatterable, area: f32, p: Vec3, n: Vec3) -> f32 {
+    if area <= 0.0 {
+        return 0.0;
+    }
+    let hit = Hit::new(1.0, p, n, mat, 0.5, 0.5);
+    let front = mat.emitted(&Ray::new(p + n, -n, 0.0), &hit);
+    let back = mat.emitted(&Ray::new(p - n, n, 0.0), &hit);
+    PI * area * (front.luminance() + back.luminance())
+}
+
+/// Returns a random direction from origin towards the sphere with the given
+/// center and radius, uniformly distributed over the solid angle of the sphere.
+pub fn sphere_random(center: Vec3, radius: f32, origin: Vec3) -> Vec3 {
+    let dir = center - origin;
+    let sq_dist = dir.sq_len();
+    if sq_dist <= radius * radius {
+        // every direction leads to the sphere from inside
+        return rand_unit_vector();
+    }
+    ONB::from_w(dir).local(rand_to_sphere(radius, sq_dist))
+}
+
+/// Returns the density with which sphere_random returns a direction, given
+/// that the direction hits the sphere.
+pub fn sphere_pdf(center: Vec3, radius: f32, origin: Vec3) -> f32 {
+    let sq_dist = (center - origin).sq_len();
+    if sq_dist <= radius * radius {
+        return 1.0 / (4.0 * PI);
+    }
+
+    let cos_theta_max = (1.0 - radius * radius / sq_dist).sqrt();
+    1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use image::{ImageFormat, RgbImage};
+
+use crate::math::Vec3;
+use crate::strategy::Tile;
+
+/// Framebuffer holds the linear colors of a rendered image. Pixels are
+/// addressed by (x, y), with (0, 0) being the top left corner.
+#[derive(Clone)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Vec3>,
+}
+
+impl Framebuffer {
+    /// Returns a new black framebuffer with the given resolution.
+    pub fn new(width: u32, height: u32) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![Vec3(0.0, 0.0, 0.0); width as usize * height as usize],
+        }
+    }
+
+    /// Returns the width of the framebuffer in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the framebuffer in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the color of the pixel at (x, y).
+    pub fn get(&self, x: u32, y: u32) -> Vec3 {
+        self.pixels[self.index(x, y)]
+    }
+
+    /// Sets the color of the pixel at (x, y).
+    pub fn set(&mut self, x: u32, y: u32, col: Vec3) {
+        let idx = self.index(x, y);
+        self.pixels[idx] = col;
+    }
+
+    /// Copies the pixels of a rendered tile into the framebuffer.
+    /// Panics if the tile does not fit into the framebuffer.
+    pub fn merge(&mut self, film: &FilmTile) {
+        let tile = film.tile;
+        assert!(tile.x + tile.width <= self.width && tile.y + tile.height <= self.height);
+
+        let width = tile.width as usize;
+        for (row, src) in film.pixels.chunks_exact(width.max(1)).enumerate() {
+            let start = self.index(tile.x, tile.y + row as u32);
+            self.pixels[start..start + width].copy_from_slice(src);
+        }
+    }
+
+    /// Returns an 8-bit image of the framebuffer, gamma corrected with gamma 2
+    /// and clamped to [0, 1].
+    pub fn to_image(&self) -> RgbImage {
+        let mut buf = RgbImage::new(self.width, self.height);
+        for (x, y, pixel) in buf.enumerate_pixels_mut() {
+            let col = self.get(x, y);
+
+            let r = (255.99 * col.0.sqrt().min(1.0)) as u8;
+            let g = (255.99 * col.1.sqrt().min(1.0)) as u8;
+            let b = (255.99 * col.2.sqrt().min(1.0)) as u8;
+
+            *pixel = image::Rgb([r, g, b]);
+        }
+        buf
+    }
+
+    /// Saves the framebuffer as an image with the given format.
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: ImageFormat) -> std::io::Result<()> {
+        self.to_image().save_with_format(path, format)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+/// FilmTile holds the colors of a single tile while it is being rendered.
+/// Each worker renders into its own tile, which is merged into the
+/// framebuffer once it is done, so no pixel is shared between threads.
+pub struct FilmTile {
+    tile: Tile,
+    pixels: Vec<Vec3>,
+}
+
+impl FilmTile {
+    /// Returns a new black film for the given tile.
+    pub fn new(tile: Tile) -> FilmTile {
+        FilmTile {
+            tile,
+            pixels: vec![Vec3(0.0, 0.0, 0.0); tile.len()],
+        }
+    }
+
+    /// Returns the tile that the film covers.
+    pub fn tile(&self) -> Tile {
+        self.tile
+    }
+
+    /// Sets the color of the pixel at (x, y), given in framebuffer coordinates.
+    pub fn set(&mut self, x: u32, y: u32, col: Vec3) {
+        let idx =
+            (y - self.tile.y) as usize * self.tile.width as usize + (x - self.tile.x) as usize;
+        self.pixels[idx] = col;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_tiles() {
+        let mut fb = Framebuffer::new(5, 4);
+        let tile = Tile {
+            x: 3,
+            y: 1,
+            width: 2,
+            height: 3,
+        };
+        let mut film = FilmTile::new(tile);
+        for (x, y) in tile.pixels() {
+            film.set(x, y, Vec3(x as f32, y as f32, 1.0));
+        }
+        fb.merge(&film);
+
+        for y in 0..4 {
+            for x in 0..5 {
+                let expected = if x >= 3 && y >= 1 {
+                    Vec3(x as f32, y as f32, 1.0)
+                } else {
+                    Vec3(0.0, 0.0, 0.0)
+                };
+                assert_eq!(fb.get(x, y), expected);
+            }
+        }
+    }
+}
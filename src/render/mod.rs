@@ -0,0 +1,5 @@
+pub use framebuffer::{FilmTile, Framebuffer};
+pub use renderer::{RenderSettings, Renderer};
+
+mod framebuffer;
+mod renderer;
@@ -0,0 +1,306 @@
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use threadpool::ThreadPool;
+
+use crate::integrator::Integrator;
+use crate::math::Vec3;
+use crate::render::{FilmTile, Framebuffer};
+use crate::scene::Scene;
+use crate::strategy::Bucket;
+use crate::utils::rng::{self, uniform_in_range};
+
+/// RenderSettings holds the parameters of a render:
+///     width, height - resolution of the output image in pixels
+///     samples - maximum number of samples per pixel
+///     min_samples - number of samples taken for every pixel before the noise
+///                   threshold is checked, so that a few dark samples do not
+///                   stop a pixel; with min_samples >= samples every pixel
+///                   takes all samples
+///     threads - number of worker threads
+///     buckets - minimum number of buckets the image is split into
+///     noise_threshold - sampling of a pixel stops once a new sample changes
+///                       the average of its samples by less than the
+///                       threshold in every channel
+///     seed - if set, the random generators are seeded per bucket, which
+///            makes renders reproducible regardless of thread scheduling
+///     max_depth - maximum number of bounces of a path
+///     rr_depth - number of bounces after which paths are terminated
+///                randomly, depending on their throughput (Russian roulette)
+#[derive(Clone, Debug)]
+pub struct RenderSettings {
+    pub width: u32,
+    pub height: u32,
+    pub samples: u32,
+    pub min_samples: u32,
+    pub threads: usize,
+    pub buckets: u32,
+    pub noise_threshold: f32,
+    pub seed: Option<u64>,
+    pub max_depth: u32,
+    pub rr_depth: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> RenderSettings {
+        RenderSettings {
+            width: 1000,
+            height: 1000,
+            samples: 100,
+            min_samples: 16,
+            threads: 3,
+            buckets: 4,
+            noise_threshold: 0.0001,
+            seed: None,
+            max_depth: 50,
+            rr_depth: 5,
+        }
+    }
+}
+
+/// Renderer traces rays from the camera of a scene through the scene and
+/// collects the results in a framebuffer. The color of every ray is computed
+/// by the integrator. The work is split into buckets that are rendered in
+/// parallel.
+pub struct Renderer {
+    scene: Arc<Scene>,
+    integrator: Arc<dyn Integrator>,
+    settings: RenderSettings,
+}
+
+impl Renderer {
+    /// Returns a new renderer for the given scene and integrator, using the
+    /// given settings.
+    pub fn new(
+        scene: Scene,
+        integrator: Arc<dyn Integrator>,
+        settings: RenderSettings,
+    ) -> Renderer {
+        Renderer {
+            scene: Arc::new(scene),
+            integrator,
+            settings,
+        }
+    }
+
+    /// Accessor for renderer.settings.
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    /// Renders the scene and returns the resulting framebuffer. Blocks until
+    /// all buckets are done.
+    pub fn render(&self) -> Framebuffer {
+        let nx = self.settings.width;
+        let ny = self.settings.height;
+        let strategy = Bucket::new(nx, ny, self.settings.buckets);
+        let pool = ThreadPool::new(self.settings.threads);
+
+        // finished tiles are sent back to this thread, which merges them
+        let (sender, receiver) = mpsc::channel();
+        let mut tiles = 0;
+        for (bucket, tile) in strategy.enumerate() {
+            let scene = Arc::clone(&self.scene);
+            let integrator = Arc::clone(&self.integrator);
+            let settings = self.settings.clone();
+            let sender = sender.clone();
+            tiles += 1;
+
+            pool.execute(move || {
+                if let Some(seed) = settings.seed {
+                    rng::seed(seed.wrapping_add(bucket as u64));
+                }
+
+                let mut film = FilmTile::new(tile);
+                for (x, y) in tile.pixels() {
+                    let col = sample_pixel(&scene, integrator.as_ref(), &settings, x, y);
+                    film.set(x, y, col);
+                }
+                sender.send(film).expect("renderer stopped receiving tiles");
+            });
+        }
+        drop(sender);
+
+        // the loop ends once every worker is done and has dropped its sender
+        let mut fb = Framebuffer::new(nx, ny);
+        let mut merged = 0;
+        for film in receiver {
+            fb.merge(&film);
+            merged += 1;
+        }
+        assert_eq!(merged, tiles, "a render worker panicked");
+        fb
+    }
+}
+
+/// Returns the average color of the samples taken for the pixel at (x, y).
+/// Every sample is jittered within the pixel.
+fn sample_pixel(
+    scene: &Scene,
+    integrator: &dyn Integrator,
+    settings: &RenderSettings,
+    x: u32,
+    y: u32,
+) -> Vec3 {
+    let nx = settings.width;
+    let ny = settings.height;
+    let nthreshold = settings.noise_threshold;
+
+    let mut avg = Vec3(0.0, 0.0, 0.0);
+    for i in 0..settings.samples.max(1) {
+        let u = (x as f32 + uniform_in_range(0.0, 1.0)) / nx as f32;
+        let v = ((ny - y) as f32 + uniform_in_range(0.0, 1.0)) / ny as f32;
+        let col = integrator.li(&scene.camera.get_ray(u, v), scene);
+        let newavg = avg + (col - avg) / (i + 1) as f32;
+        if i + 1 >= settings.min_samples
+            && (newavg.0 - avg.0).abs() < nthreshold
+            && (newavg.1 - avg.1).abs() < nthreshold
+            && (newavg.2 - avg.2).abs() < nthreshold
+        {
+            // noise threshold reached
+            return newavg;
+        }
+        avg = newavg;
+    }
+    avg
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::background::ConstBackground;
+    use crate::integrator::PathIntegrator;
+    use crate::mtl::LightDiffuse;
+    use crate::obj::Sphere;
+    use crate::tex::ConstTexture;
+    use crate::trace::{BVHNode, Camera, HitVec, Hittable, Ray};
+
+    fn camera() -> Camera {
+        Camera::new(
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 0.0, -1.0),
+            Vec3(0.0, 1.0, 0.0),
+            90.0,
+            8.0 / 6.0,
+            0.0,
+            1.0,
+            0.0,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_render_empty_scene() {
+        let settings = RenderSettings {
+            width: 8,
+            height: 6,
+            samples: 4,
+            threads: 2,
+            ..RenderSettings::default()
+        };
+        let scene = Scene::new(
+            camera(),
+            Arc::new(HitVec::new(Vec::new())),
+            Vec::new(),
+            Arc::new(ConstBackground::new(Vec3(0.25, 0.5, 1.0))),
+        );
+        let integrator = PathIntegrator::new(settings.max_depth, settings.rr_depth);
+        let renderer = Renderer::new(scene, Arc::new(integrator), settings);
+
+        let fb = renderer.render();
+        assert_eq!(fb.width(), 8);
+        assert_eq!(fb.height(), 6);
+        for y in 0..6 {
+            for x in 0..8 {
+                assert_eq!(fb.get(x, y), Vec3(0.25, 0.5, 1.0));
+            }
+        }
+
+        let img = fb.to_image();
+        assert_eq!(img.get_pixel(0, 0).0, [127, 181, 255]);
+    }
+
+    #[test]
+    fn test_render_bvh_scene() {
+        let settings = RenderSettings {
+            width: 8,
+            height: 6,
+            samples: 4,
+            threads: 4,
+            buckets: 6,
+            ..RenderSettings::default()
+        };
+        let light = LightDiffuse::new(ConstTexture::new(Vec3(1.0, 0.5, 0.25)), 1.0, true);
+        let world: Vec<Arc<dyn Hittable>> = vec![
+            Arc::new(Sphere::new(Vec3(0.0, 0.0, -2.0), 1.0, light)),
+            Arc::new(HitVec::new(Vec::new())),
+        ];
+        let scene = Scene::new(
+            camera(),
+            Arc::new(BVHNode::new(world, 0.0, 1.0)),
+            Vec::new(),
+            Arc::new(ConstBackground::new(Vec3(0.0, 0.0, 0.0))),
+        );
+        assert_eq!(scene.lights.len(), 1);
+        let integrator = PathIntegrator::new(settings.max_depth, settings.rr_depth);
+        let renderer = Renderer::new(scene, Arc::new(integrator), settings);
+
+        let fb = renderer.render();
+        assert_eq!(fb.get(4, 3), Vec3(1.0, 0.5, 0.25));
+        assert_eq!(fb.get(0, 0), Vec3(0.0, 0.0, 0.0));
+    }
+
+    /// FirstDark is an integrator whose first sample is black and all others
+    /// are white.
+    struct FirstDark {
+        calls: AtomicUsize,
+    }
+
+    impl Integrator for FirstDark {
+        fn li(&self, _: &Ray, _: &Scene) -> Vec3 {
+            match self.calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Vec3(0.0, 0.0, 0.0),
+                _ => Vec3(1.0, 1.0, 1.0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_pixel() {
+        let settings = RenderSettings {
+            width: 8,
+            height: 6,
+            samples: 32,
+            min_samples: 16,
+            ..RenderSettings::default()
+        };
+        let scene = Scene::new(
+            camera(),
+            Arc::new(HitVec::new(Vec::new())),
+            Vec::new(),
+            Arc::new(ConstBackground::new(Vec3(0.0, 0.0, 0.0))),
+        );
+        let integrator = FirstDark {
+            calls: AtomicUsize::new(0),
+        };
+
+        // a dark first sample does not stop the pixel
+        let col = sample_pixel(&scene, &integrator, &settings, 4, 3);
+        assert_eq!(integrator.calls.load(Ordering::SeqCst), 32);
+        assert!((col.0 - 31.0 / 32.0).abs() < 1e-5);
+
+        // without a minimum, the dark first sample stops the pixel
+        let settings = RenderSettings {
+            min_samples: 0,
+            ..settings
+        };
+        let integrator = FirstDark {
+            calls: AtomicUsize::new(0),
+        };
+        let col = sample_pixel(&scene, &integrator, &settings, 4, 3);
+        assert_eq!(integrator.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(col, Vec3(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// Pos is a position in a scene file. Both line and column start from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+/// SceneError is returned when a scene description could not be loaded.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene file could not be read.
+    Io(std::io::Error),
+    /// The scene description is malformed at the given position.
+    Syntax { pos: Pos, msg: String },
+}
+
+impl SceneError {
+    pub(crate) fn syntax<S: Into<String>>(pos: Pos, msg: S) -> SceneError {
+        SceneError::Syntax {
+            pos,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "{}", err),
+            SceneError::Syntax { pos, msg } => {
+                write!(f, "line {}, column {}: {}", pos.line, pos.col, msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<std::io::Error> for SceneError {
+    fn from(err: std::io::Error) -> SceneError {
+        SceneError::Io(err)
+    }
+}
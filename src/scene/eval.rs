@@ -0,0 +1,702 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::background::{Background, ConstBackground, EnvMap, GradientBackground, PreethamSky};
+use crate::light::{DirectionalLight, Light, PointLight, SpotLight};
+use crate::math::Vec3;
+use crate::mtl::{
+    Conductor, Dielectric, Emissive, Isotropic, Lambertian, LightDiffuse, Medium, Metal,
+    RoughDielectric, Scatterable,
+};
+use crate::obj::transform::{FlipNormals, RotateY, Translate};
+use crate::obj::{ConstDensity, MovSphere, RectBox, Sphere, XYRect, XZRect, YZRect};
+use crate::tex::{CheckerTexture, ConstTexture, ImageTexture, Wrappable};
+use crate::trace::{BVHNode, Camera, Hittable};
+
+use super::error::{Pos, SceneError};
+use super::parser::{Call, Expr, Stmt};
+use super::wavefront::load_obj;
+use super::Scene;
+
+/// Value is the result of evaluating an expression.
+#[derive(Clone)]
+enum Value {
+    Num(f32),
+    Str(String),
+    List(Vec<Value>),
+    Texture(Arc<dyn Wrappable>),
+    Material(Arc<dyn Scatterable>),
+    Object(Arc<dyn Hittable>),
+    Camera(Camera),
+    Background(Arc<dyn Background>),
+    Light(Arc<dyn Light>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Num(_) => "number",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Texture(_) => "texture",
+            Value::Material(_) => "material",
+            Value::Object(_) => "object",
+            Value::Camera(_) => "camera",
+            Value::Background(_) => "background",
+            Value::Light(_) => "light",
+        }
+    }
+}
+
+/// Evaluator executes the statements of a scene description and collects
+/// the camera, the background, the lights and the objects of the scene.
+pub struct Evaluator<'a> {
+    base_dir: &'a Path,
+    aspect: f32,
+    names: HashMap<String, Value>,
+    camera: Option<Camera>,
+    background: Option<Arc<dyn Background>>,
+    lights: Vec<Arc<dyn Light>>,
+    objects: Vec<Arc<dyn Hittable>>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Returns a new evaluator. Relative file paths are resolved against
+    /// base_dir and aspect is used as the default camera aspect ratio.
+    pub fn new(base_dir: &'a Path, aspect: f32) -> Evaluator<'a> {
+        Evaluator {
+            base_dir,
+            aspect,
+            names: HashMap::new(),
+            camera: None,
+            background: None,
+            lights: Vec::new(),
+            objects: Vec::new(),
+        }
+    }
+
+    /// Executes the statements and returns the resulting scene. The end
+    /// position is used for errors about missing statements.
+    pub fn eval(mut self, stmts: Vec<Stmt>, end: Pos) -> Result<Scene, SceneError> {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Let(name, _, expr) => {
+                    let value = self.expr(&expr)?;
+                    self.names.insert(name, value);
+                }
+                Stmt::Expr(expr) => self.add(&expr)?,
+            }
+        }
+
+        let camera = self
+            .camera
+            .ok_or_else(|| SceneError::syntax(end, "scene has no camera"))?;
+
+        // the objects of the scene are gathered in a single BVH over the
+        // camera's shutter interval
+        let (time_begin, time_end) = camera.shutter();
+        let world = Arc::new(BVHNode::new(self.objects, time_begin, time_end));
+        let background = self
+            .background
+            .unwrap_or_else(|| Arc::new(ConstBackground::new(Vec3(0.0, 0.0, 0.0))));
+        Ok(Scene::new(camera, world, self.lights, background))
+    }
+
+    fn add(&mut self, expr: &Expr) -> Result<(), SceneError> {
+        match self.expr(expr)? {
+            Value::Object(obj) => self.objects.push(obj),
+            Value::Light(light) => self.lights.push(light),
+            Value::Camera(cam) => {
+                if self.camera.is_some() {
+                    return Err(SceneError::syntax(expr.pos(), "camera is already defined"));
+                }
+                self.camera = Some(cam);
+            }
+            Value::Background(background) => {
+                if self.background.is_some() {
+                    return Err(SceneError::syntax(
+                        expr.pos(),
+                        "background is already defined",
+                    ));
+                }
+                self.background = Some(background);
+            }
+            value => {
+                return Err(SceneError::syntax(
+                    expr.pos(),
+                    format!(
+                        "expected camera, background, light or object, found {}",
+                        value.kind()
+                    ),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn expr(&self, expr: &Expr) -> Result<Value, SceneError> {
+        match expr {
+            Expr::Num(n, _) => Ok(Value::Num(*n)),
+            Expr::Str(s, _) => Ok(Value::Str(s.clone())),
+            Expr::List(items, _) => items
+                .iter()
+                .map(|item| self.expr(item))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List),
+            Expr::Ident(name, pos) => self
+                .names
+                .get(name)
+                .cloned()
+                .ok_or_else(|| SceneError::syntax(*pos, format!("unknown name `{}`", name))),
+            Expr::Call(call) => self.call(call),
+        }
+    }
+
+    fn call(&self, call: &Call) -> Result<Value, SceneError> {
+        match call.name.as_str() {
+            "camera" => {
+                let mut args = self.bind(
+                    call,
+                    &[
+                        "lookfrom",
+                        "lookat",
+                        "vup",
+                        "fov",
+                        "aperture",
+                        "focus_dist",
+                        "time0",
+                        "time1",
+                        "aspect",
+                    ],
+                )?;
+                let lookfrom = args.vec3("lookfrom")?;
+                let lookat = args.vec3("lookat")?;
+                let vup = args.vec3_or("vup", Vec3(0.0, 1.0, 0.0))?;
+                let fov = args.num_or("fov", 40.0)?;
+                let aperture = args.num_or("aperture", 0.0)?;
+                let focus_dist = args.num_or("focus_dist", (lookfrom - lookat).len())?;
+                let time0 = args.num_or("time0", 0.0)?;
+                let time1 = args.num_or("time1", 1.0)?;
+                let aspect = args.num_or("aspect", self.aspect)?;
+                Ok(Value::Camera(Camera::new(
+                    lookfrom, lookat, vup, fov, aspect, aperture, focus_dist, time0, time1,
+                )))
+            }
+            "background" => {
+                let mut args = self.bind(call, &["color"])?;
+                Ok(Value::Background(Arc::new(ConstBackground::new(
+                    args.vec3("color")?,
+                ))))
+            }
+            "gradient" => {
+                let mut args = self.bind(call, &["bottom", "top"])?;
+                Ok(Value::Background(Arc::new(GradientBackground::new(
+                    args.vec3("bottom")?,
+                    args.vec3("top")?,
+                ))))
+            }
+            "env_map" => {
+                let mut args = self.bind(call, &["path", "rotation", "intensity"])?;
+                let (path, pos) = args.string("path")?;
+                let rotation = args.num_or("rotation", 0.0)?;
+                let intensity = args.num_or("intensity", 1.0)?;
+                let full_path = self.base_dir.join(&path);
+                let env = EnvMap::load(full_path, rotation, intensity).map_err(|err| {
+                    let msg = format!("failed to load environment map `{}`: {}", path, err);
+                    SceneError::syntax(pos, msg)
+                })?;
+                Ok(Value::Background(Arc::new(env)))
+            }
+            "sky" => {
+                let mut args =
+                    self.bind(call, &["elevation", "azimuth", "turbidity", "intensity"])?;
+                Ok(Value::Background(Arc::new(PreethamSky::new(
+                    args.num("elevation")?,
+                    args.num_or("azimuth", 0.0)?,
+                    args.num_or("turbidity", 3.0)?,
+                    args.num_or("intensity", 1.0)?,
+                ))))
+            }
+
+            // lights
+            "point_light" => {
+                let mut args = self.bind(call, &["position", "intensity"])?;
+                Ok(Value::Light(Arc::new(PointLight::new(
+                    args.vec3("position")?,
+                    args.vec3("intensity")?,
+                ))))
+            }
+            "spot_light" => {
+                let mut args = self.bind(
+                    call,
+                    &["position", "target", "intensity", "angle", "falloff"],
+                )?;
+                let position = args.vec3("position")?;
+                let (target, pos) = args.required("target")?;
+                let target = Args::to_vec3("target", target, pos)?;
+                if target == position {
+                    return Err(SceneError::syntax(
+                        pos,
+                        "`target` must differ from `position`",
+                    ));
+                }
+                Ok(Value::Light(Arc::new(SpotLight::new(
+                    position,
+                    target - position,
+                    args.vec3("intensity")?,
+                    args.num_or("angle", 30.0)?,
+                    args.num_or("falloff", 5.0)?,
+                ))))
+            }
+            "directional_light" => {
+                let mut args = self.bind(call, &["direction", "irradiance"])?;
+                Ok(Value::Light(Arc::new(DirectionalLight::new(
+                    args.vec3("direction")?,
+                    args.vec3("irradiance")?,
+                ))))
+            }
+
+            // textures
+            "const" => {
+                let mut args = self.bind(call, &["color"])?;
+                Ok(Value::Texture(Arc::new(ConstTexture::new(
+                    args.vec3("color")?,
+                ))))
+            }
+            "checker" => {
+                let mut args = self.bind(call, &["odd", "even"])?;
+                Ok(Value::Texture(Arc::new(CheckerTexture::new(
+                    args.texture("odd")?,
+                    args.texture("even")?,
+                ))))
+            }
+            "image" => {
+                let mut args = self.bind(call, &["path"])?;
+                let (path, pos) = args.string("path")?;
+                let img = image::open(self.base_dir.join(&path)).map_err(|err| {
+                    SceneError::syntax(pos, format!("failed to load image `{}`: {}", path, err))
+                })?;
+                Ok(Value::Texture(Arc::new(ImageTexture::new(img))))
+            }
+
+            // materials
+            "lambertian" => {
+                let mut args = self.bind(call, &["albedo"])?;
+                Ok(Value::Material(Arc::new(Lambertian::new(
+                    args.texture("albedo")?,
+                ))))
+            }
+            "metal" => {
+                let mut args = self.bind(call, &["albedo", "fuzz"])?;
+                Ok(Value::Material(Arc::new(Metal::new(
+                    args.vec3("albedo")?,
+                    args.num_or("fuzz", 0.0)?,
+                ))))
+            }
+            "conductor" => {
+                let mut args = self.bind(call, &["eta", "k", "roughness"])?;
+                let (eta, k) = match args.required("eta")? {
+                    (Value::Str(name), pos) => {
+                        if let Some((_, pos)) = args.take("k") {
+                            return Err(SceneError::syntax(
+                                pos,
+                                format!("`k` is given by the preset `{}`", name),
+                            ));
+                        }
+                        Conductor::preset(&name).ok_or_else(|| {
+                            SceneError::syntax(pos, format!("unknown metal `{}`", name))
+                        })?
+                    }
+                    (value, pos) => (Args::to_vec3("eta", value, pos)?, args.vec3("k")?),
+                };
+                Ok(Value::Material(Arc::new(Conductor::new(
+                    eta,
+                    k,
+                    args.num_or("roughness", 0.0)?,
+                ))))
+            }
+            "dielectric" => {
+                let mut args = self.bind(call, &["ior", "albedo", "absorption", "priority"])?;
+                let medium = args.medium()?;
+                let albedo = args.texture_or("albedo", Vec3(1.0, 1.0, 1.0))?;
+                Ok(Value::Material(Arc::new(Dielectric::with_medium(
+                    medium, albedo,
+                ))))
+            }
+            "rough_dielectric" => {
+                let mut args = self.bind(
+                    call,
+                    &["ior", "roughness", "albedo", "absorption", "priority"],
+                )?;
+                let medium = args.medium()?;
+                let roughness = args.num_or("roughness", 0.0)?;
+                let albedo = args.texture_or("albedo", Vec3(1.0, 1.0, 1.0))?;
+                Ok(Value::Material(Arc::new(RoughDielectric::with_medium(
+                    medium, roughness, albedo,
+                ))))
+            }
+            "isotropic" => {
+                let mut args = self.bind(call, &["albedo"])?;
+                Ok(Value::Material(Arc::new(Isotropic::new(
+                    args.texture("albedo")?,
+                ))))
+            }
+            "light" => {
+                let mut args =
+                    self.bind(call, &["emit", "intensity", "temperature", "two_sided"])?;
+                let emit = args.texture_or("emit", Vec3(1.0, 1.0, 1.0))?;
+                let intensity = args.num_or("intensity", 1.0)?;
+                let two_sided = args.flag_or("two_sided", true)?;
+                Ok(Value::Material(match args.num_opt("temperature")? {
+                    Some(temperature) => Arc::new(LightDiffuse::blackbody(
+                        emit,
+                        temperature,
+                        intensity,
+                        two_sided,
+                    )),
+                    None => Arc::new(LightDiffuse::new(emit, intensity, two_sided)),
+                }))
+            }
+            "emissive" => {
+                let mut args = self.bind(call, &["material", "emission"])?;
+                Ok(Value::Material(Arc::new(Emissive::new(
+                    args.material("material")?,
+                    args.material("emission")?,
+                ))))
+            }
+
+            // objects
+            "sphere" => {
+                let mut args = self.bind(call, &["center", "radius", "material"])?;
+                Ok(Value::Object(Arc::new(Sphere::new(
+                    args.vec3("center")?,
+                    args.num("radius")?,
+                    args.material("material")?,
+                ))))
+            }
+            "moving_sphere" => {
+                let mut args = self.bind(
+                    call,
+                    &["center0", "time0", "center1", "time1", "radius", "material"],
+                )?;
+                Ok(Value::Object(Arc::new(MovSphere::new(
+                    args.vec3("center0")?,
+                    args.num("time0")?,
+                    args.vec3("center1")?,
+                    args.num("time1")?,
+                    args.num("radius")?,
+                    args.material("material")?,
+                ))))
+            }
+            "xy_rect" => {
+                let mut args = self.bind(call, &["x0", "y0", "x1", "y1", "k", "material"])?;
+                Ok(Value::Object(Arc::new(XYRect::new(
+                    args.num("x0")?,
+                    args.num("y0")?,
+                    args.num("x1")?,
+                    args.num("y1")?,
+                    args.num("k")?,
+                    args.material("material")?,
+                ))))
+            }
+            "xz_rect" => {
+                let mut args = self.bind(call, &["x0", "z0", "x1", "z1", "k", "material"])?;
+                Ok(Value::Object(Arc::new(XZRect::new(
+                    args.num("x0")?,
+                    args.num("z0")?,
+                    args.num("x1")?,
+                    args.num("z1")?,
+                    args.num("k")?,
+                    args.material("material")?,
+                ))))
+            }
+            "yz_rect" => {
+                let mut args = self.bind(call, &["y0", "z0", "y1", "z1", "k", "material"])?;
+                Ok(Value::Object(Arc::new(YZRect::new(
+                    args.num("y0")?,
+                    args.num("z0")?,
+                    args.num("y1")?,
+                    args.num("z1")?,
+                    args.num("k")?,
+                    args.material("material")?,
+                ))))
+            }
+            "box" => {
+                let mut args = self.bind(call, &["min", "max", "material"])?;
+                Ok(Value::Object(Arc::new(RectBox::new(
+                    args.vec3("min")?,
+                    args.vec3("max")?,
+                    args.material("material")?,
+                ))))
+            }
+            "const_density" => {
+                let mut args = self.bind(call, &["boundary", "density", "phase"])?;
+                Ok(Value::Object(Arc::new(ConstDensity::new(
+                    args.object("boundary")?,
+                    args.num("density")?,
+                    args.material("phase")?,
+                ))))
+            }
+
+            "mesh" => {
+                let mut args = self.bind(call, &["path", "material"])?;
+                let (path, pos) = args.string("path")?;
+                let mat = args.material("material")?;
+                let mesh = load_obj(self.base_dir.join(&path), mat).map_err(|err| {
+                    SceneError::syntax(pos, format!("failed to load mesh `{}`: {}", path, err))
+                })?;
+                if mesh.is_empty() {
+                    return Err(SceneError::syntax(
+                        pos,
+                        format!("mesh `{}` has no faces", path),
+                    ));
+                }
+
+                let triangles = mesh
+                    .triangles()
+                    .into_iter()
+                    .map(|tri| Arc::new(tri) as Arc<dyn Hittable>)
+                    .collect();
+                Ok(Value::Object(Arc::new(BVHNode::new(triangles, 0.0, 1.0))))
+            }
+
+            // transforms
+            "translate" => {
+                let mut args = self.bind(call, &["object", "offset"])?;
+                Ok(Value::Object(Arc::new(Translate::new(
+                    args.object("object")?,
+                    args.vec3("offset")?,
+                ))))
+            }
+            "rotate_y" => {
+                let mut args = self.bind(call, &["object", "angle"])?;
+                Ok(Value::Object(Arc::new(RotateY::new(
+                    args.object("object")?,
+                    args.num("angle")?,
+                ))))
+            }
+            "flip_normals" => {
+                let mut args = self.bind(call, &["object"])?;
+                Ok(Value::Object(Arc::new(FlipNormals::new(
+                    args.object("object")?,
+                ))))
+            }
+
+            name => Err(SceneError::syntax(
+                call.pos,
+                format!("unknown function `{}`", name),
+            )),
+        }
+    }
+
+    /// Evaluates the arguments of a call and binds them to the given
+    /// parameter names.
+    fn bind(&self, call: &Call, params: &[&'static str]) -> Result<Args, SceneError> {
+        if call.args.len() > params.len() {
+            return Err(SceneError::syntax(
+                call.args[params.len()].pos(),
+                format!("`{}` takes at most {} arguments", call.name, params.len()),
+            ));
+        }
+
+        let mut values: Vec<Option<(Value, Pos)>> = vec![None; params.len()];
+        for (i, arg) in call.args.iter().enumerate() {
+            values[i] = Some((self.expr(arg)?, arg.pos()));
+        }
+        for (name, pos, arg) in &call.named {
+            let i = params.iter().position(|p| p == name).ok_or_else(|| {
+                SceneError::syntax(
+                    *pos,
+                    format!("unknown argument `{}` for `{}`", name, call.name),
+                )
+            })?;
+            if values[i].is_some() {
+                return Err(SceneError::syntax(
+                    *pos,
+                    format!("argument `{}` is given more than once", name),
+                ));
+            }
+            values[i] = Some((self.expr(arg)?, arg.pos()));
+        }
+
+        Ok(Args {
+            call: call.name.clone(),
+            pos: call.pos,
+            params: params.to_vec(),
+            values,
+        })
+    }
+}
+
+/// Args holds the bound arguments of a call and converts them to the
+/// types expected by the builtins.
+struct Args {
+    call: String,
+    pos: Pos,
+    params: Vec<&'static str>,
+    values: Vec<Option<(Value, Pos)>>,
+}
+
+impl Args {
+    fn take(&mut self, param: &str) -> Option<(Value, Pos)> {
+        let i = self
+            .params
+            .iter()
+            .position(|p| *p == param)
+            .expect("unknown parameter");
+        self.values[i].take()
+    }
+
+    fn required(&mut self, param: &str) -> Result<(Value, Pos), SceneError> {
+        let (call, pos) = (&self.call, self.pos);
+        let missing =
+            SceneError::syntax(pos, format!("missing argument `{}` for `{}`", param, call));
+        self.take(param).ok_or(missing)
+    }
+
+    fn mismatch(param: &str, expected: &str, value: &Value, pos: Pos) -> SceneError {
+        SceneError::syntax(
+            pos,
+            format!(
+                "expected {} for `{}`, found {}",
+                expected,
+                param,
+                value.kind()
+            ),
+        )
+    }
+
+    fn to_num(param: &str, value: Value, pos: Pos) -> Result<f32, SceneError> {
+        match value {
+            Value::Num(n) => Ok(n),
+            value => Err(Self::mismatch(param, "number", &value, pos)),
+        }
+    }
+
+    fn to_vec3(param: &str, value: Value, pos: Pos) -> Result<Vec3, SceneError> {
+        if let Value::List(items) = &value {
+            if let [Value::Num(x), Value::Num(y), Value::Num(z)] = items.as_slice() {
+                return Ok(Vec3(*x, *y, *z));
+            }
+        }
+        Err(Self::mismatch(param, "list of 3 numbers", &value, pos))
+    }
+
+    fn to_texture(param: &str, value: Value, pos: Pos) -> Result<Arc<dyn Wrappable>, SceneError> {
+        match value {
+            Value::Texture(tex) => Ok(tex),
+            Value::List(_) => {
+                let col = Self::to_vec3(param, value, pos)?;
+                Ok(Arc::new(ConstTexture::new(col)))
+            }
+            value => Err(Self::mismatch(param, "texture or color", &value, pos)),
+        }
+    }
+
+    fn num(&mut self, param: &str) -> Result<f32, SceneError> {
+        let (value, pos) = self.required(param)?;
+        Self::to_num(param, value, pos)
+    }
+
+    fn num_or(&mut self, param: &str, default: f32) -> Result<f32, SceneError> {
+        match self.take(param) {
+            Some((value, pos)) => Self::to_num(param, value, pos),
+            None => Ok(default),
+        }
+    }
+
+    fn num_opt(&mut self, param: &str) -> Result<Option<f32>, SceneError> {
+        match self.take(param) {
+            Some((value, pos)) => Self::to_num(param, value, pos).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether the number given for param is not 0.
+    fn flag_or(&mut self, param: &str, default: bool) -> Result<bool, SceneError> {
+        match self.take(param) {
+            Some((value, pos)) => Self::to_num(param, value, pos).map(|n| n != 0.0),
+            None => Ok(default),
+        }
+    }
+
+    fn vec3(&mut self, param: &str) -> Result<Vec3, SceneError> {
+        let (value, pos) = self.required(param)?;
+        Self::to_vec3(param, value, pos)
+    }
+
+    fn vec3_or(&mut self, param: &str, default: Vec3) -> Result<Vec3, SceneError> {
+        match self.take(param) {
+            Some((value, pos)) => Self::to_vec3(param, value, pos),
+            None => Ok(default),
+        }
+    }
+
+    fn string(&mut self, param: &str) -> Result<(String, Pos), SceneError> {
+        match self.required(param)? {
+            (Value::Str(s), pos) => Ok((s, pos)),
+            (value, pos) => Err(Self::mismatch(param, "string", &value, pos)),
+        }
+    }
+
+    fn texture(&mut self, param: &str) -> Result<Arc<dyn Wrappable>, SceneError> {
+        let (value, pos) = self.required(param)?;
+        Self::to_texture(param, value, pos)
+    }
+
+    fn texture_or(&mut self, param: &str, default: Vec3) -> Result<Arc<dyn Wrappable>, SceneError> {
+        match self.take(param) {
+            Some((value, pos)) => Self::to_texture(param, value, pos),
+            None => Ok(Arc::new(ConstTexture::new(default))),
+        }
+    }
+
+    /// Returns the medium given by the ior, absorption and priority arguments
+    /// of a dielectric.
+    fn medium(&mut self) -> Result<Medium, SceneError> {
+        let (value, pos) = self.required("ior")?;
+        let ior = Self::to_num("ior", value, pos)?;
+        if ior <= 0.0 {
+            return Err(SceneError::syntax(pos, "`ior` must be positive"));
+        }
+        let absorption = match self.take("absorption") {
+            Some((value, pos)) => {
+                let v = Self::to_vec3("absorption", value, pos)?;
+                if v.0 < 0.0 || v.1 < 0.0 || v.2 < 0.0 {
+                    return Err(SceneError::syntax(pos, "`absorption` must not be negative"));
+                }
+                v
+            }
+            None => Vec3(0.0, 0.0, 0.0),
+        };
+        let priority = match self.take("priority") {
+            Some((value, pos)) => {
+                let n = Self::to_num("priority", value, pos)?;
+                if n < 0.0 || n.fract() != 0.0 {
+                    return Err(SceneError::syntax(
+                        pos,
+                        "`priority` must be a non-negative integer",
+                    ));
+                }
+                n as u32
+            }
+            None => 0,
+        };
+        Ok(Medium::new(ior, absorption, priority))
+    }
+
+    fn material(&mut self, param: &str) -> Result<Arc<dyn Scatterable>, SceneError> {
+        match self.required(param)? {
+            (Value::Material(mat), _) => Ok(mat),
+            (value, pos) => Err(Self::mismatch(param, "material", &value, pos)),
+        }
+    }
+
+    fn object(&mut self, param: &str) -> Result<Arc<dyn Hittable>, SceneError> {
+        match self.required(param)? {
+            (Value::Object(obj), _) => Ok(obj),
+            (value, pos) => Err(Self::mismatch(param, "object", &value, pos)),
+        }
+    }
+}
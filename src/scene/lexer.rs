@@ -0,0 +1,261 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::error::{Pos, SceneError};
+
+/// Token is a single lexical element of a scene description.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Num(f32),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Eq,
+    Eof,
+}
+
+impl Token {
+    /// Returns a human readable description of the token for error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Num(n) => format!("number {}", n),
+            Token::Str(s) => format!("string \"{}\"", s),
+            Token::LParen => "`(`".to_string(),
+            Token::RParen => "`)`".to_string(),
+            Token::LBracket => "`[`".to_string(),
+            Token::RBracket => "`]`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Eq => "`=`".to_string(),
+            Token::Eof => "end of file".to_string(),
+        }
+    }
+}
+
+/// Lexer splits a scene description into tokens. Whitespace and comments,
+/// starting with `#` and running until the end of the line, are skipped.
+pub struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(src: &'a str) -> Lexer<'a> {
+        Lexer {
+            chars: src.chars().peekable(),
+            line: 1,
+            col: 1,
+        }
+    }
+
+    /// Returns all tokens of the input together with their positions.
+    /// The last token is always Token::Eof.
+    pub fn tokenize(mut self) -> Result<Vec<(Token, Pos)>, SceneError> {
+        let mut tokens = Vec::new();
+        loop {
+            let (token, pos) = self.next_token()?;
+            let done = token == Token::Eof;
+            tokens.push((token, pos));
+            if done {
+                break Ok(tokens);
+            }
+        }
+    }
+
+    fn pos(&self) -> Pos {
+        Pos {
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_blank(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '#' {
+                while let Some(&c) = self.chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.bump();
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<(Token, Pos), SceneError> {
+        self.skip_blank();
+        let pos = self.pos();
+        let c = match self.chars.peek() {
+            Some(&c) => c,
+            None => return Ok((Token::Eof, pos)),
+        };
+
+        let token = match c {
+            '(' => self.single(Token::LParen),
+            ')' => self.single(Token::RParen),
+            '[' => self.single(Token::LBracket),
+            ']' => self.single(Token::RBracket),
+            ',' => self.single(Token::Comma),
+            '=' => self.single(Token::Eq),
+            '"' => self.string(pos)?,
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => self.number(pos)?,
+            c if c.is_alphabetic() || c == '_' => self.ident(),
+            c => {
+                return Err(SceneError::syntax(
+                    pos,
+                    format!("unexpected character `{}`", c),
+                ))
+            }
+        };
+
+        Ok((token, pos))
+    }
+
+    fn single(&mut self, token: Token) -> Token {
+        self.bump();
+        token
+    }
+
+    fn string(&mut self, pos: Pos) -> Result<Token, SceneError> {
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => break Ok(Token::Str(s)),
+                Some('\n') | None => break Err(SceneError::syntax(pos, "unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self, pos: Pos) -> Result<Token, SceneError> {
+        let mut s = String::new();
+        while let Some(&c) = self.chars.peek() {
+            let exp_sign = (c == '-' || c == '+') && (s.ends_with('e') || s.ends_with('E'));
+            let sign = (c == '-' || c == '+') && s.is_empty();
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || sign || exp_sign {
+                s.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        s.parse::<f32>()
+            .map(Token::Num)
+            .map_err(|_| SceneError::syntax(pos, format!("invalid number `{}`", s)))
+    }
+
+    fn ident(&mut self) -> Token {
+        let mut s = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                s.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        Token::Ident(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<(Token, (usize, usize))> {
+        Lexer::new(src)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|(token, pos)| (token, (pos.line, pos.col)))
+            .collect()
+    }
+
+    fn error(src: &str) -> (usize, usize, String) {
+        match Lexer::new(src).tokenize() {
+            Err(SceneError::Syntax { pos, msg }) => (pos.line, pos.col, msg),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("let m = metal([1, -0.5, 2e-1], fuzz = .3)"),
+            vec![
+                (Token::Ident("let".to_string()), (1, 1)),
+                (Token::Ident("m".to_string()), (1, 5)),
+                (Token::Eq, (1, 7)),
+                (Token::Ident("metal".to_string()), (1, 9)),
+                (Token::LParen, (1, 14)),
+                (Token::LBracket, (1, 15)),
+                (Token::Num(1.0), (1, 16)),
+                (Token::Comma, (1, 17)),
+                (Token::Num(-0.5), (1, 19)),
+                (Token::Comma, (1, 23)),
+                (Token::Num(0.2), (1, 25)),
+                (Token::RBracket, (1, 29)),
+                (Token::Comma, (1, 30)),
+                (Token::Ident("fuzz".to_string()), (1, 32)),
+                (Token::Eq, (1, 37)),
+                (Token::Num(0.3), (1, 39)),
+                (Token::RParen, (1, 41)),
+                (Token::Eof, (1, 42)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_lines() {
+        assert_eq!(
+            tokens("# a comment\n  image(\"earth map.jpg\") # another\n"),
+            vec![
+                (Token::Ident("image".to_string()), (2, 3)),
+                (Token::LParen, (2, 8)),
+                (Token::Str("earth map.jpg".to_string()), (2, 9)),
+                (Token::RParen, (2, 24)),
+                (Token::Eof, (3, 1)),
+            ]
+        );
+        assert_eq!(tokens(""), vec![(Token::Eof, (1, 1))]);
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert_eq!(
+            error("sphere(\n  $)"),
+            (2, 3, "unexpected character `$`".to_string())
+        );
+        assert_eq!(
+            error("image(\"earth.jpg)\n"),
+            (1, 7, "unterminated string".to_string())
+        );
+        assert_eq!(
+            error("[1, 2..5]"),
+            (1, 5, "invalid number `2..5`".to_string())
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Scene description format.
+//!
+//! A scene file is a list of statements. `let` binds a value to a name and
+//! every other statement adds a camera, a background, a light or an object to
+//! the scene. Values are numbers, strings, lists (`[x, y, z]` for vectors and
+//! colors) and calls to builtins, which take positional arguments followed
+//! by named ones. Comments start with `#`.
+//!
+//! ```text
+//! camera(lookfrom = [278, 278, -800], lookat = [278, 278, 0], fov = 40)
+//! background([0, 0, 0])
+//!
+//! let white = lambertian([0.73, 0.73, 0.73])
+//! xz_rect(213, 227, 343, 332, 554, light([15, 15, 15]))
+//! translate(rotate_y(box([0, 0, 0], [165, 165, 165], white), -18), [130, 0, 65])
+//! ```
+//!
+//! Builtins:
+//!     camera(lookfrom, lookat, vup, fov, aperture, focus_dist, time0, time1, aspect)
+//!     background(color), gradient(bottom, top),
+//!     env_map(path, rotation, intensity),
+//!     sky(elevation, azimuth, turbidity, intensity)
+//!     point_light(position, intensity),
+//!     spot_light(position, target, intensity, angle, falloff),
+//!     directional_light(direction, irradiance)
+//!     const(color), checker(odd, even), image(path)
+//!     lambertian(albedo), metal(albedo, fuzz),
+//!     dielectric(ior, albedo, absorption, priority),
+//!     rough_dielectric(ior, roughness, albedo, absorption, priority),
+//!     conductor(eta, k, roughness),
+//!     isotropic(albedo), light(emit, intensity, temperature, two_sided),
+//!     emissive(material, emission)
+//!     sphere(center, radius, material),
+//!     moving_sphere(center0, time0, center1, time1, radius, material),
+//!     xy_rect(x0, y0, x1, y1, k, material), xz_rect(x0, z0, x1, z1, k, material),
+//!     yz_rect(y0, z0, y1, z1, k, material), box(min, max, material),
+//!     const_density(boundary, density, phase), mesh(path, material)
+//!     translate(object, offset), rotate_y(object, angle), flip_normals(object)
+//!
+//! Colors are accepted wherever a texture is expected. Image, environment map
+//! and mesh paths are relative to the directory of the scene file. Meshes are read from
+//! Wavefront OBJ files.
+//!
+//! Flags like two_sided are numbers, where 0 is false. A light with a
+//! temperature in Kelvin emits the color of a blackbody, tinted with emit.
+//! Emissive adds the light of its emission, e.g. a light, to a material that
+//! keeps scattering.
+//!
+//! The absorption of a dielectric is the fraction of red, green and blue light
+//! absorbed per unit of distance inside, so thick glass is darker than thin.
+//! Dielectrics may be nested, e.g. ice in water, and refract with the ratio of
+//! the indices on either side. Where they overlap, the one with the highest
+//! priority fills the space, so water can overlap the walls of a glass with a
+//! higher priority instead of leaving a gap of air between them.
+//!
+//! A conductor is a rough metal with the complex index of refraction eta + ik.
+//! Instead of colors for eta and k, eta can name a metal: "gold", "silver",
+//! "copper" or "aluminium".
+
+pub use error::{Pos, SceneError};
+pub use wavefront::{load_obj, parse_obj};
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::background::Background;
+use crate::light::{AreaLight, EnvLight, Light, LightBVH};
+use crate::trace::{Camera, Hittable};
+
+use eval::Evaluator;
+use lexer::Lexer;
+use parser::Parser;
+
+mod error;
+mod eval;
+mod lexer;
+mod parser;
+mod wavefront;
+
+/// Scene holds everything that is needed to render an image:
+///     camera - camera the image is seen from
+///     world - root object that holds all objects of the scene
+///     lights - light sources that are sampled directly
+///     background - light arriving from outside of the scene
+pub struct Scene {
+    pub camera: Camera,
+    pub world: Arc<dyn Hittable>,
+    pub lights: LightBVH,
+    pub background: Arc<dyn Background>,
+}
+
+impl Scene {
+    /// Returns a new scene with the given lights, e.g. point lights. Every
+    /// emissive object in the world becomes an area light, and a background
+    /// that can be sampled becomes a light too.
+    pub fn new(
+        camera: Camera,
+        world: Arc<dyn Hittable>,
+        lights: Vec<Arc<dyn Light>>,
+        background: Arc<dyn Background>,
+    ) -> Scene {
+        let mut shapes = Vec::new();
+        if world.is_emissive() {
+            shapes.push(Arc::clone(&world));
+        } else {
+            world.lights(&mut shapes);
+        }
+        let mut all: Vec<Arc<dyn Light>> = shapes
+            .into_iter()
+            .map(|shape| Arc::new(AreaLight::new(shape)) as Arc<dyn Light>)
+            .collect();
+        all.extend(lights);
+        if background.can_sample() {
+            all.push(Arc::new(EnvLight::new(Arc::clone(&background))));
+        }
+
+        Scene {
+            camera,
+            world,
+            lights: LightBVH::new(all),
+            background,
+        }
+    }
+}
+
+/// Loads the scene description from the given file. Aspect is the
+/// width/height ratio used for the camera, unless the scene overrides it.
+pub fn load<P: AsRef<Path>>(path: P, aspect: f32) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let src = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse(&src, base_dir, aspect)
+}
+
+/// Parses the given scene description. Relative paths in the description
+/// are resolved against base_dir.
+pub fn parse(src: &str, base_dir: &Path, aspect: f32) -> Result<Scene, SceneError> {
+    let tokens = Lexer::new(src).tokenize()?;
+    let end = tokens[tokens.len() - 1].1;
+    let stmts = Parser::new(tokens).parse()?;
+    Evaluator::new(base_dir, aspect).eval(stmts, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec3;
+    use crate::trace::Ray;
+
+    fn parse_str(src: &str) -> Result<Scene, SceneError> {
+        parse(src, Path::new(""), 1.0)
+    }
+
+    fn syntax_error(src: &str) -> (usize, usize, String) {
+        match parse_str(src) {
+            Err(SceneError::Syntax { pos, msg }) => (pos.line, pos.col, msg),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_cornell() {
+        let scene = parse_str(include_str!("../../example/cornell/cornell.rsrt")).unwrap();
+
+        let ray = scene.camera.get_ray(0.5, 0.5);
+        let dir = ray.direction().as_unit();
+        assert_eq!(scene.background.radiance(dir), Vec3(0.0, 0.0, 0.0));
+        let hit = scene.world.hit(&ray, 0.001, f32::MAX);
+        assert!(hit.is_some());
+        assert_eq!(scene.lights.len(), 1);
+    }
+
+    #[test]
+    fn test_load_mesh() {
+        let scene = load("example/mesh/mesh.rsrt", 1.0).unwrap();
+        let ray = Ray::new(Vec3(0.0, 0.0, 10.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = scene.world.hit(&ray, 0.001, f32::MAX).unwrap();
+        let expected = 10.0 - 1.0 / (30.0f32).to_radians().cos();
+        assert!((hit.t() - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_parse_named_args() {
+        let scene = parse_str(
+            "camera([0, 0, 0], [0, 0, -1], fov = 90)\n\
+             let s = sphere(center = [0, 0, -5], radius = 1, material = lambertian(const([1, 0, 0])))\n\
+             s",
+        )
+        .unwrap();
+
+        let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = scene.world.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(hit.t(), 4.0);
+    }
+
+    #[test]
+    fn test_parse_backgrounds() {
+        let scene = parse_str(
+            "camera([0, 0, 0], [0, 0, -1])\n\
+             gradient([1, 1, 1], top = [0.5, 0.7, 1])",
+        )
+        .unwrap();
+        let up = scene.background.radiance(Vec3(0.0, 1.0, 0.0));
+        assert_eq!(up, Vec3(0.5, 0.7, 1.0));
+
+        // the rest of the message comes from the OS
+        let (line, col, msg) =
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nenv_map(\"missing.hdr\", intensity = 2)");
+        assert_eq!((line, col), (2, 9));
+        assert!(msg.starts_with("failed to load environment map `missing.hdr`: "));
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nbackground([0, 0, 0])\ngradient([0, 0, 0], [1, 1, 1])"),
+            (3, 1, "background is already defined".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_lights() {
+        let scene = parse_str(
+            "camera([0, 0, 0], [0, 0, -1])\n\
+             point_light([0, 4, 0], [16, 16, 16])\n\
+             spot_light([0, 4, 0], target = [0, 0, 0], intensity = [1, 1, 1], angle = 20)\n\
+             directional_light([0, -1, 0], [2, 2, 2])\n\
+             xz_rect(-1, -1, 1, 1, 3, light([1, 1, 1], intensity = 2, two_sided = 0))",
+        )
+        .unwrap();
+        assert_eq!(scene.lights.len(), 4);
+        assert_eq!(scene.lights.iter().filter(|l| l.is_delta()).count(), 3);
+
+        // the rectangle only emits upwards, along its normal
+        let rect = scene.lights.get(0).unwrap();
+        let above = rect.sample(Vec3(0.0, 5.0, 0.0), 0.0).unwrap();
+        assert_eq!(above.radiance, Vec3(2.0, 2.0, 2.0));
+        let below = rect.sample(Vec3(0.0, 0.0, 0.0), 0.0).unwrap();
+        assert_eq!(below.radiance, Vec3(0.0, 0.0, 0.0));
+
+        // area lights come first, then the lights in the order of the file
+        let point = scene.lights.get(1).unwrap();
+        let ls = point.sample(Vec3(0.0, 0.0, 0.0), 0.0).unwrap();
+        assert_eq!(ls.radiance, Vec3(1.0, 1.0, 1.0));
+        assert_eq!(ls.dist, 4.0);
+        let spot = scene.lights.get(2).unwrap();
+        assert!(spot.sample(Vec3(0.0, 0.0, 0.0), 0.0).is_some());
+        assert!(spot.sample(Vec3(4.0, 0.0, 0.0), 0.0).is_none());
+
+        assert_eq!(
+            syntax_error(
+                "camera([0, 0, 0], [0, 0, -1])\nspot_light([0, 4, 0], [0, 4, 0], [1, 1, 1])"
+            ),
+            (2, 23, "`target` must differ from `position`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, $)"),
+            (2, 22, "unexpected character `$`".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1"),
+            (2, 20, "expected `,`, found end of file".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\n  sphere([0, 0], 1, metal([1, 1, 1]))"),
+            (
+                2,
+                10,
+                "expected list of 3 numbers for `center`, found list".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, conductor(\"tin\"))"),
+            (2, 32, "unknown metal `tin`".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, dielectric(1.5, priority = 0.5))"),
+            (2, 49, "`priority` must be a non-negative integer".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, dielectric(0))"),
+            (2, 33, "`ior` must be positive".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, dielectric(1.5, absorption = [0, -1, 0]))"),
+            (2, 51, "`absorption` must not be negative".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1, white)"),
+            (2, 22, "unknown name `white`".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nsphere([0, 0, 0], 1)"),
+            (2, 1, "missing argument `material` for `sphere`".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1])\nlambertian([1, 1, 1])"),
+            (
+                2,
+                1,
+                "expected camera, background, light or object, found material".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("let m = metal([1, 1, 1])\n"),
+            (2, 1, "scene has no camera".to_string())
+        );
+        assert_eq!(
+            syntax_error("camera([0, 0, 0], [0, 0, -1], zoom = 2)"),
+            (1, 31, "unknown argument `zoom` for `camera`".to_string())
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use super::error::{Pos, SceneError};
+use super::lexer::Token;
+
+/// Expr is an expression from a scene description:
+///     Num - a number, e.g. `1.5`
+///     Str - a string, e.g. `"earthmap.jpg"`
+///     List - a list of expressions, e.g. `[0, 1, 0]`
+///     Ident - a reference to a named value, e.g. `white`
+///     Call - a call to a builtin, e.g. `sphere([0, 0, 0], 1, white)`
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Num(f32, Pos),
+    Str(String, Pos),
+    List(Vec<Expr>, Pos),
+    Ident(String, Pos),
+    Call(Call),
+}
+
+impl Expr {
+    /// Returns the position where the expression starts.
+    pub fn pos(&self) -> Pos {
+        match self {
+            Expr::Num(_, pos) | Expr::Str(_, pos) | Expr::List(_, pos) | Expr::Ident(_, pos) => {
+                *pos
+            }
+            Expr::Call(call) => call.pos,
+        }
+    }
+}
+
+/// Call is a call to a builtin with positional and named arguments.
+/// Named arguments always follow the positional ones.
+#[derive(Debug, PartialEq)]
+pub struct Call {
+    pub name: String,
+    pub pos: Pos,
+    pub args: Vec<Expr>,
+    pub named: Vec<(String, Pos, Expr)>,
+}
+
+/// Stmt is a top level statement from a scene description:
+///     Let - binds a value to a name, e.g. `let white = lambertian([1, 1, 1])`
+///     Expr - adds the value to the scene, e.g. `sphere([0, 0, 0], 1, white)`
+#[derive(Debug, PartialEq)]
+pub enum Stmt {
+    Let(String, Pos, Expr),
+    Expr(Expr),
+}
+
+/// Parser builds statements out of the tokens of a scene description.
+pub struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    current: usize,
+}
+
+impl Parser {
+    /// Returns a new parser for the given tokens. The last token is expected
+    /// to be Token::Eof.
+    pub fn new(tokens: Vec<(Token, Pos)>) -> Parser {
+        Parser { tokens, current: 0 }
+    }
+
+    /// Parses all statements until the end of the input.
+    pub fn parse(mut self) -> Result<Vec<Stmt>, SceneError> {
+        let mut stmts = Vec::new();
+        while self.peek() != &Token::Eof {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current].0
+    }
+
+    fn peek_pos(&self) -> Pos {
+        self.tokens[self.current].1
+    }
+
+    fn peek_next(&self) -> &Token {
+        let idx = (self.current + 1).min(self.tokens.len() - 1);
+        &self.tokens[idx].0
+    }
+
+    fn advance(&mut self) -> (Token, Pos) {
+        let item = self.tokens[self.current].clone();
+        if item.0 != Token::Eof {
+            self.current += 1;
+        }
+        item
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<Pos, SceneError> {
+        let (token, pos) = self.advance();
+        if token == expected {
+            Ok(pos)
+        } else {
+            Err(SceneError::syntax(
+                pos,
+                format!(
+                    "expected {}, found {}",
+                    expected.describe(),
+                    token.describe()
+                ),
+            ))
+        }
+    }
+
+    fn ident(&mut self) -> Result<(String, Pos), SceneError> {
+        match self.advance() {
+            (Token::Ident(name), pos) => Ok((name, pos)),
+            (token, pos) => Err(SceneError::syntax(
+                pos,
+                format!("expected a name, found {}", token.describe()),
+            )),
+        }
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, SceneError> {
+        if self.peek() == &Token::Ident("let".to_string()) {
+            self.advance();
+            let (name, pos) = self.ident()?;
+            self.expect(Token::Eq)?;
+            let expr = self.expr()?;
+            return Ok(Stmt::Let(name, pos, expr));
+        }
+
+        Ok(Stmt::Expr(self.expr()?))
+    }
+
+    fn expr(&mut self) -> Result<Expr, SceneError> {
+        match self.advance() {
+            (Token::Num(n), pos) => Ok(Expr::Num(n, pos)),
+            (Token::Str(s), pos) => Ok(Expr::Str(s, pos)),
+            (Token::LBracket, pos) => {
+                let items = self.list(Token::RBracket)?;
+                Ok(Expr::List(items, pos))
+            }
+            (Token::Ident(name), pos) => {
+                if self.peek() == &Token::LParen {
+                    self.advance();
+                    self.call(name, pos)
+                } else {
+                    Ok(Expr::Ident(name, pos))
+                }
+            }
+            (token, pos) => Err(SceneError::syntax(
+                pos,
+                format!("expected an expression, found {}", token.describe()),
+            )),
+        }
+    }
+
+    fn list(&mut self, close: Token) -> Result<Vec<Expr>, SceneError> {
+        let mut items = Vec::new();
+        while self.peek() != &close {
+            items.push(self.expr()?);
+            if self.peek() != &close {
+                self.expect(Token::Comma)?;
+            }
+        }
+        self.advance();
+        Ok(items)
+    }
+
+    fn call(&mut self, name: String, pos: Pos) -> Result<Expr, SceneError> {
+        let mut args = Vec::new();
+        let mut named = Vec::new();
+        while self.peek() != &Token::RParen {
+            let is_named = matches!(self.peek(), Token::Ident(_)) && self.peek_next() == &Token::Eq;
+            if is_named {
+                let (arg_name, arg_pos) = self.ident()?;
+                self.advance();
+                named.push((arg_name, arg_pos, self.expr()?));
+            } else if !named.is_empty() {
+                return Err(SceneError::syntax(
+                    self.peek_pos(),
+                    "positional arguments must come before named ones",
+                ));
+            } else {
+                args.push(self.expr()?);
+            }
+
+            if self.peek() != &Token::RParen {
+                self.expect(Token::Comma)?;
+            }
+        }
+        self.advance();
+
+        Ok(Expr::Call(Call {
+            name,
+            pos,
+            args,
+            named,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::lexer::Lexer;
+
+    fn parse(src: &str) -> Result<Vec<Stmt>, SceneError> {
+        Parser::new(Lexer::new(src).tokenize()?).parse()
+    }
+
+    fn pos(line: usize, col: usize) -> Pos {
+        Pos { line, col }
+    }
+
+    fn error(src: &str) -> (usize, usize, String) {
+        match parse(src) {
+            Err(SceneError::Syntax { pos, msg }) => (pos.line, pos.col, msg),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_stmts() {
+        let stmts =
+            parse("let white = lambertian([1, 1, 1])\nsphere([0, 0, 0], 1, white, two_sided = 0)")
+                .unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                Stmt::Let(
+                    "white".to_string(),
+                    pos(1, 5),
+                    Expr::Call(Call {
+                        name: "lambertian".to_string(),
+                        pos: pos(1, 13),
+                        args: vec![Expr::List(
+                            vec![
+                                Expr::Num(1.0, pos(1, 25)),
+                                Expr::Num(1.0, pos(1, 28)),
+                                Expr::Num(1.0, pos(1, 31)),
+                            ],
+                            pos(1, 24),
+                        )],
+                        named: vec![],
+                    }),
+                ),
+                Stmt::Expr(Expr::Call(Call {
+                    name: "sphere".to_string(),
+                    pos: pos(2, 1),
+                    args: vec![
+                        Expr::List(
+                            vec![
+                                Expr::Num(0.0, pos(2, 9)),
+                                Expr::Num(0.0, pos(2, 12)),
+                                Expr::Num(0.0, pos(2, 15)),
+                            ],
+                            pos(2, 8),
+                        ),
+                        Expr::Num(1.0, pos(2, 19)),
+                        Expr::Ident("white".to_string(), pos(2, 22)),
+                    ],
+                    named: vec![(
+                        "two_sided".to_string(),
+                        pos(2, 29),
+                        Expr::Num(0.0, pos(2, 41)),
+                    )],
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_trailing_comma() {
+        let stmts = parse("image(\"earth.jpg\",)").unwrap();
+        match &stmts[0] {
+            Stmt::Expr(Expr::Call(call)) => {
+                assert_eq!(
+                    call.args,
+                    vec![Expr::Str("earth.jpg".to_string(), pos(1, 7))]
+                );
+            }
+            stmt => panic!("unexpected statement: {:?}", stmt),
+        }
+        assert_eq!(parse("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            error("let = 1"),
+            (1, 5, "expected a name, found `=`".to_string())
+        );
+        assert_eq!(
+            error("let m metal([1, 1, 1])"),
+            (1, 7, "expected `=`, found `metal`".to_string())
+        );
+        assert_eq!(
+            error("sphere([0, 0 0], 1, white)"),
+            (1, 14, "expected `,`, found number 0".to_string())
+        );
+        assert_eq!(
+            error("sphere(, 1)"),
+            (1, 8, "expected an expression, found `,`".to_string())
+        );
+        assert_eq!(
+            error("metal(fuzz = 0.5, [1, 1, 1])"),
+            (
+                1,
+                19,
+                "positional arguments must come before named ones".to_string()
+            )
+        );
+        assert_eq!(
+            error("[1, 2"),
+            (1, 6, "expected `,`, found end of file".to_string())
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::math::Vec3;
+use crate::mtl::Scatterable;
+use crate::obj::TriangleMesh;
+
+use super::error::{Pos, SceneError};
+
+/// Loads the Wavefront OBJ file at the given path and returns it as a
+/// triangle mesh with the given material.
+pub fn load_obj<P: AsRef<Path>, M: Scatterable>(
+    path: P,
+    mat: M,
+) -> Result<TriangleMesh<M>, SceneError> {
+    let file = File::open(path)?;
+    parse_obj(BufReader::new(file), mat)
+}
+
+/// Parses Wavefront OBJ data and returns it as a triangle mesh with the given
+/// material. Vertex positions (`v`), texture coordinates (`vt`), normals (`vn`)
+/// and faces (`f`) are read, including negative (relative) indices. Polygons
+/// are split into triangle fans, so they are expected to be convex.
+/// Normals and texture coordinates are used only if all faces specify them.
+/// Other statements, such as groups and material libraries, are ignored.
+pub fn parse_obj<R: BufRead, M: Scatterable>(
+    reader: R,
+    mat: M,
+) -> Result<TriangleMesh<M>, SceneError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut faces = Vec::new();
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = fields(line, idx + 1);
+        let (keyword, pos) = match fields.next() {
+            Some(field) => field,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => positions.push(vec3(&mut fields, pos)?),
+            "vn" => normals.push(vec3(&mut fields, pos)?),
+            "vt" => {
+                let u = number(fields.next(), pos)?;
+                let v = match fields.next() {
+                    Some(field) => number(Some(field), pos)?,
+                    None => 0.0,
+                };
+                uvs.push((u, v));
+            }
+            "f" => {
+                let mut corners = Vec::new();
+                for (field, pos) in fields {
+                    corners.push(corner(
+                        field,
+                        pos,
+                        positions.len(),
+                        uvs.len(),
+                        normals.len(),
+                    )?);
+                }
+                if corners.len() < 3 {
+                    return Err(SceneError::syntax(pos, "face needs at least 3 vertices"));
+                }
+
+                for i in 1..corners.len() - 1 {
+                    faces.push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(build_mesh(&positions, &uvs, &normals, &faces, mat))
+}
+
+/// Corner is a face vertex given by its indices into the position, texture
+/// coordinate and normal lists.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    p: usize,
+    uv: Option<usize>,
+    n: Option<usize>,
+}
+
+/// Returns a mesh for the given faces. Corners that reference the same
+/// attributes are merged into a single mesh vertex.
+fn build_mesh<M: Scatterable>(
+    positions: &[Vec3],
+    uvs: &[(f32, f32)],
+    normals: &[Vec3],
+    faces: &[[Corner; 3]],
+    mat: M,
+) -> TriangleMesh<M> {
+    let corners = || faces.iter().flat_map(|face| face.iter());
+    let use_uvs = corners().all(|c| c.uv.is_some());
+    let use_normals = corners().all(|c| c.n.is_some());
+
+    let mut vertices = HashMap::new();
+    let mut mesh_positions = Vec::new();
+    let mut mesh_uvs = Vec::new();
+    let mut mesh_normals = Vec::new();
+    let mut indices = Vec::with_capacity(faces.len());
+    for face in faces {
+        let mut tri = [0; 3];
+        for (i, c) in face.iter().enumerate() {
+            let key = Corner {
+                p: c.p,
+                uv: c.uv.filter(|_| use_uvs),
+                n: c.n.filter(|_| use_normals),
+            };
+            tri[i] = *vertices.entry(key).or_insert_with(|| {
+                mesh_positions.push(positions[key.p]);
+                if let Some(uv) = key.uv {
+                    mesh_uvs.push(uvs[uv]);
+                }
+                if let Some(n) = key.n {
+                    mesh_normals.push(normals[n]);
+                }
+                (mesh_positions.len() - 1) as u32
+            });
+        }
+        indices.push(tri);
+    }
+
+    TriangleMesh::new(mesh_positions, mesh_normals, mesh_uvs, indices, mat)
+}
+
+/// Returns the whitespace separated fields of the line with their positions.
+fn fields(line: &str, line_no: usize) -> impl Iterator<Item = (&str, Pos)> {
+    line.split_whitespace().map(move |field| {
+        let offset = field.as_ptr() as usize - line.as_ptr() as usize;
+        let col = line[..offset].chars().count() + 1;
+        (field, Pos { line: line_no, col })
+    })
+}
+
+fn number(field: Option<(&str, Pos)>, stmt_pos: Pos) -> Result<f32, SceneError> {
+    match field {
+        Some((field, pos)) => field
+            .parse()
+            .map_err(|_| SceneError::syntax(pos, format!("invalid number `{}`", field))),
+        None => Err(SceneError::syntax(stmt_pos, "missing number")),
+    }
+}
+
+fn vec3<'a, I: Iterator<Item = (&'a str, Pos)>>(
+    fields: &mut I,
+    pos: Pos,
+) -> Result<Vec3, SceneError> {
+    Ok(Vec3(
+        number(fields.next(), pos)?,
+        number(fields.next(), pos)?,
+        number(fields.next(), pos)?,
+    ))
+}
+
+/// Parses a face vertex in one of the forms `v`, `v/vt`, `v//vn` and `v/vt/vn`,
+/// given the current lengths of the position, texture coordinate and normal lists.
+fn corner(
+    field: &str,
+    pos: Pos,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<Corner, SceneError> {
+    let mut refs = field.split('/');
+    let p = match refs.next() {
+        Some(idx) => index(idx, positions, pos)?,
+        None => return Err(SceneError::syntax(pos, "missing vertex index")),
+    };
+    let uv = match refs.next() {
+        Some("") | None => None,
+        Some(idx) => Some(index(idx, uvs, pos)?),
+    };
+    let n = match refs.next() {
+        Some("") | None => None,
+        Some(idx) => Some(index(idx, normals, pos)?),
+    };
+    if refs.next().is_some() {
+        return Err(SceneError::syntax(
+            pos,
+            format!("invalid face vertex `{}`", field),
+        ));
+    }
+
+    Ok(Corner { p, uv, n })
+}
+
+/// Resolves a 1-based OBJ index, which is relative to the end of the
+/// list when negative, into an index of a list with length len.
+fn index(idx: &str, len: usize, pos: Pos) -> Result<usize, SceneError> {
+    let parsed: i64 = idx
+        .parse()
+        .map_err(|_| SceneError::syntax(pos, format!("invalid index `{}`", idx)))?;
+    let resolved = if parsed < 0 {
+        len as i64 + parsed
+    } else {
+        parsed - 1
+    };
+
+    if parsed == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(SceneError::syntax(
+            pos,
+            format!("index {} is out of range", idx),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::ScatterRecord;
+    use crate::trace::{Hit, Hittable, Ray};
+
+    const QUAD: &str = "\
+# unit quad with texture coordinates
+o quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn test_parse_obj() {
+        let mesh = parse_obj(QUAD.as_bytes(), TestMaterial {}).unwrap();
+        assert_eq!(mesh.len(), 2);
+        let tris = mesh.triangles();
+
+        let ray = Ray::new(Vec3(0.25, 0.75, 1.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = tris
+            .iter()
+            .find_map(|tri| tri.hit(&ray, 0.0, 10.0))
+            .unwrap();
+        assert_eq!(hit.t(), 1.0);
+        assert_eq!(hit.n(), Vec3(0.0, 0.0, 1.0));
+        assert!((hit.u() - 0.25).abs() < 1e-6);
+        assert!((hit.v() - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_obj_negative_indices() {
+        let src = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\nv 0 0 1\nf 1// -1 3";
+        let mesh = parse_obj(src.as_bytes(), TestMaterial {}).unwrap();
+        assert_eq!(mesh.len(), 2);
+        assert_eq!(
+            mesh.positions(0),
+            [Vec3(0.0, 0.0, 0.0), Vec3(1.0, 0.0, 0.0), Vec3(0.0, 1.0, 0.0)]
+        );
+        // -1 refers to the vertex defined last before the face
+        assert_eq!(
+            mesh.positions(1),
+            [Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 1.0), Vec3(0.0, 1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_parse_obj_errors() {
+        let err = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n".as_bytes(), TestMaterial {}).err();
+        assert_eq!(
+            err.unwrap().to_string(),
+            "line 3, column 7: index 3 is out of range"
+        );
+
+        let err = parse_obj("v 0 0 zero\n".as_bytes(), TestMaterial {}).err();
+        assert_eq!(
+            err.unwrap().to_string(),
+            "line 1, column 7: invalid number `zero`"
+        );
+
+        let err = parse_obj("v 0 0 0\n  f 1 1\n".as_bytes(), TestMaterial {}).err();
+        assert_eq!(
+            err.unwrap().to_string(),
+            "line 2, column 3: face needs at least 3 vertices"
+        );
+    }
+
+    struct TestMaterial {}
+
+    impl Scatterable for TestMaterial {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
+            None
+        }
+    }
+}
@@ -2,7 +2,7 @@
 /// buckets for a given region. The count may vary if buckets
 /// do not fit perfectly into the region.
 /// For example, a pixel space of 1920x1080 could be split into
-/// 10 buckets of size 192x108.
+/// 10 buckets of size 192x108. Each bucket is yielded as a Tile.
 pub struct Bucket {
     x: u32,
     y: u32,
@@ -13,6 +13,35 @@ pub struct Bucket {
     done: bool,
 }
 
+/// Tile is a rectangular region of pixels, given by its top left corner
+/// (x, y) and its size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    /// Returns the number of pixels in the tile.
+    pub fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Returns whether the tile has no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the (x, y) coordinates of the tile's pixels row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32)> {
+        let tile = *self;
+        (tile.y..tile.y + tile.height)
+            .flat_map(move |y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+    }
+}
+
 impl Bucket {
     pub fn new(x: u32, y: u32, count: u32) -> Bucket {
         Bucket {
@@ -27,7 +56,7 @@ impl Bucket {
 }
 
 impl Iterator for Bucket {
-    type Item = Vec<(u32, u32)>;
+    type Item = Tile;
 
     fn next(&mut self) -> Option<Self::Item> {
         let count_sqrt = (self.count as f64).sqrt();
@@ -38,22 +67,14 @@ impl Iterator for Bucket {
             return None;
         }
 
-        let mut result = Vec::new();
-        for y in 1..=by {
-            let curr_y = self.current_y * by + (y - 1);
-            if curr_y >= self.y {
-                continue;
-            }
-
-            for x in 1..=bx {
-                let curr_x = self.current_x * bx + (x - 1);
-                if curr_x >= self.x {
-                    continue;
-                }
-
-                result.push((curr_y, curr_x))
-            }
-        }
+        let x = self.current_x * bx;
+        let y = self.current_y * by;
+        let result = Tile {
+            x,
+            y,
+            width: bx.min(self.x - x),
+            height: by.min(self.y - y),
+        };
 
         self.current_x += 1;
         if self.current_x * bx >= self.x {
@@ -72,135 +93,51 @@ impl Iterator for Bucket {
 mod tests {
     use super::*;
 
+    fn tile(x: u32, y: u32, width: u32, height: u32) -> Tile {
+        Tile {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
     #[test]
     fn test_buckets_fitting() {
         let mut bucket = Bucket::new(10, 10, 4);
-        assert_eq!(
-            bucket.next(),
-            Some(vec![
-                (0, 0),
-                (0, 1),
-                (0, 2),
-                (0, 3),
-                (0, 4),
-                (1, 0),
-                (1, 1),
-                (1, 2),
-                (1, 3),
-                (1, 4),
-                (2, 0),
-                (2, 1),
-                (2, 2),
-                (2, 3),
-                (2, 4),
-                (3, 0),
-                (3, 1),
-                (3, 2),
-                (3, 3),
-                (3, 4),
-                (4, 0),
-                (4, 1),
-                (4, 2),
-                (4, 3),
-                (4, 4),
-            ])
-        );
-
-        assert_eq!(
-            bucket.next(),
-            Some(vec![
-                (0, 5),
-                (0, 6),
-                (0, 7),
-                (0, 8),
-                (0, 9),
-                (1, 5),
-                (1, 6),
-                (1, 7),
-                (1, 8),
-                (1, 9),
-                (2, 5),
-                (2, 6),
-                (2, 7),
-                (2, 8),
-                (2, 9),
-                (3, 5),
-                (3, 6),
-                (3, 7),
-                (3, 8),
-                (3, 9),
-                (4, 5),
-                (4, 6),
-                (4, 7),
-                (4, 8),
-                (4, 9),
-            ])
-        );
+        assert_eq!(bucket.next(), Some(tile(0, 0, 5, 5)));
+        assert_eq!(bucket.next(), Some(tile(5, 0, 5, 5)));
+        assert_eq!(bucket.next(), Some(tile(0, 5, 5, 5)));
+        assert_eq!(bucket.next(), Some(tile(5, 5, 5, 5)));
 
-        assert_eq!(
-            bucket.next(),
-            Some(vec![
-                (5, 0),
-                (5, 1),
-                (5, 2),
-                (5, 3),
-                (5, 4),
-                (6, 0),
-                (6, 1),
-                (6, 2),
-                (6, 3),
-                (6, 4),
-                (7, 0),
-                (7, 1),
-                (7, 2),
-                (7, 3),
-                (7, 4),
-                (8, 0),
-                (8, 1),
-                (8, 2),
-                (8, 3),
-                (8, 4),
-                (9, 0),
-                (9, 1),
-                (9, 2),
-                (9, 3),
-                (9, 4),
-            ])
-        );
+        assert_eq!(bucket.next(), None);
+        assert_eq!(bucket.next(), None);
+        assert_eq!(bucket.next(), None);
+    }
 
+    #[test]
+    fn test_buckets_not_fitting() {
+        let tiles: Vec<Tile> = Bucket::new(10, 7, 9).collect();
         assert_eq!(
-            bucket.next(),
-            Some(vec![
-                (5, 5),
-                (5, 6),
-                (5, 7),
-                (5, 8),
-                (5, 9),
-                (6, 5),
-                (6, 6),
-                (6, 7),
-                (6, 8),
-                (6, 9),
-                (7, 5),
-                (7, 6),
-                (7, 7),
-                (7, 8),
-                (7, 9),
-                (8, 5),
-                (8, 6),
-                (8, 7),
-                (8, 8),
-                (8, 9),
-                (9, 5),
-                (9, 6),
-                (9, 7),
-                (9, 8),
-                (9, 9),
-            ])
+            tiles,
+            vec![
+                tile(0, 0, 4, 3),
+                tile(4, 0, 4, 3),
+                tile(8, 0, 2, 3),
+                tile(0, 3, 4, 3),
+                tile(4, 3, 4, 3),
+                tile(8, 3, 2, 3),
+                tile(0, 6, 4, 1),
+                tile(4, 6, 4, 1),
+                tile(8, 6, 2, 1),
+            ]
         );
+        assert_eq!(tiles.iter().map(|t| t.len()).sum::<usize>(), 70);
+    }
 
-        assert_eq!(bucket.next(), None);
-        assert_eq!(bucket.next(), None);
-        assert_eq!(bucket.next(), None);
+    #[test]
+    fn test_tile_pixels() {
+        let pixels: Vec<(u32, u32)> = tile(3, 1, 2, 2).pixels().collect();
+        assert_eq!(pixels, vec![(3, 1), (4, 1), (3, 2), (4, 2)]);
     }
 }
@@ -1,3 +1,3 @@
-pub use bucket::Bucket;
+pub use bucket::{Bucket, Tile};
 
 mod bucket;
@@ -1,12 +1,20 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
 use image::{DynamicImage, GenericImageView};
 
 /// Wrappable is a trait for textures. It is used to determine
 /// color for a given (u, v) on the output plane. 
-pub trait Wrappable {
+pub trait Wrappable: Send + Sync {
     fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3;
 }
 
+impl<W: Wrappable + ?Sized> Wrappable for Arc<W> {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        self.as_ref().value(u, v, p)
+    }
+}
+
 /// ConstTexture is a texture with homogeneous color.
 #[derive(Clone)]
 pub struct ConstTexture {
@@ -64,6 +72,7 @@ impl ImageTexture {
 }
 
 impl Wrappable for ImageTexture {
+    #[allow(clippy::unnecessary_cast, clippy::unnecessary_min_or_max)]
     fn value(&self, u: f32, v: f32, _: Vec3) -> Vec3 {
         let (nx, ny) = self.image.dimensions();
         let i = ((u * nx as f32) as u32).max(0).min(nx - 1);
@@ -1,102 +1,393 @@
+use crate::math::Vec3;
 use crate::obj::{surrounding_box, AABB};
+use crate::trace::hit::collect_lights;
 use crate::trace::{Hit, Hittable, Ray};
-use crate::utils::rng::uniform_in_range;
-use std::rc::Rc;
+use std::sync::Arc;
 
-/// BVHNode is a node from bounding volume hierarchy tree. It is used to lower the
+// number of buckets the centroid range is split into when searching for a split
+const SAH_BINS: usize = 12;
+// cost of traversing a node, relative to the cost of intersecting a primitive
+const TRAVERSAL_COST: f32 = 0.125;
+// leaves with more primitives are always split, if possible
+const MAX_LEAF_SIZE: usize = 4;
+// nodes deeper than this are split at the median instead
+const MAX_SAH_DEPTH: usize = 32;
+// large enough for the deepest tree with median splits below MAX_SAH_DEPTH
+const STACK_SIZE: usize = 64;
+
+/// BVHNode is the root of a bounding volume hierarchy tree. It is used to lower the
 /// count of objects that needs to be interacted with when dealing with specific ray.
+/// The tree is stored flattened in depth-first order, so the first child of an inner
+/// node directly follows it and only the index of the second child is kept.
 pub struct BVHNode {
+    nodes: Vec<LinearNode>,
+    elements: Vec<Arc<dyn Hittable>>,
+}
+
+/// LinearNode is a node of the flattened tree.
+///     offset - index of the first element for leaves, index of the second child otherwise
+///     count - number of elements for leaves, 0 otherwise
+///     axis - axis along which the children of an inner node are split
+struct LinearNode {
+    min: Vec3,
+    max: Vec3,
+    offset: u32,
+    count: u16,
+    axis: u8,
+}
+
+/// Primitive is a hittable with its precomputed bounding box and centroid.
+struct Primitive {
+    hittable: Arc<dyn Hittable>,
     bbox: AABB,
-    left: Rc<dyn Hittable>,
-    right: Rc<dyn Hittable>,
+    centroid: Vec3,
 }
 
 impl BVHNode {
     /// Returns the root of a new bounding volume hierarchy tree, constructed with the given
-    /// hittable objects.
-    pub fn new(elements: Vec<Rc<dyn Hittable>>, time_begin: f32, time_end: f32) -> BVHNode {
-        let mut elements = elements;
-
-        let axis = (3.0 * uniform_in_range(0.0, 1.0)) as i32;
-        if axis == 0 {
-            elements.sort_by(|h1, h2| {
-                let box1 = h1
-                    .bounding_box(0.0, 0.0);
-                let box2 = h2
-                    .bounding_box(0.0, 0.0);
-                box1.min().0.partial_cmp(&box2.min().0).unwrap()
-            });
-        } else if axis == 1 {
-            elements.sort_by(|h1, h2| {
-                let box1 = h1
-                    .bounding_box(0.0, 0.0);
-                let box2 = h2
-                    .bounding_box(0.0, 0.0);
-                box1.min().1.partial_cmp(&box2.min().1).unwrap()
-            });
-        } else {
-            elements.sort_by(|h1, h2| {
-                let box1 = h1
-                    .bounding_box(0.0, 0.0);
-                let box2 = h2
-                    .bounding_box(0.0, 0.0);
-                box1.min().2.partial_cmp(&box2.min().2).unwrap()
-            });
+    /// hittable objects. The tree is built top-down with the surface area heuristic: each
+    /// node is split where the expected cost of intersecting both children is the lowest.
+    pub fn new(elements: Vec<Arc<dyn Hittable>>, time_begin: f32, time_end: f32) -> BVHNode {
+        let prims: Vec<Primitive> = elements
+            .into_iter()
+            .map(|hittable| {
+                let bbox = hittable.bounding_box(time_begin, time_end);
+                let centroid = (bbox.min() + bbox.max()) / 2.0;
+                Primitive {
+                    hittable,
+                    bbox,
+                    centroid,
+                }
+            })
+            .collect();
+
+        let mut bvh = BVHNode {
+            nodes: Vec::with_capacity(2 * prims.len()),
+            elements: Vec::with_capacity(prims.len()),
+        };
+        if !prims.is_empty() {
+            bvh.build(prims, 0);
+        }
+        bvh
+    }
+
+    /// Appends the subtree for the given primitives and returns the index of its root.
+    fn build(&mut self, mut prims: Vec<Primitive>, depth: usize) -> usize {
+        let bbox = prims.iter().fold(AABB::new_hidden(), |res, prim| {
+            surrounding_box(res, prim.bbox.clone())
+        });
+        if prims.len() <= 1 {
+            return self.leaf(bbox, prims);
         }
 
-        let (left, right) = if elements.len() == 1 {
-            let item = elements.into_iter().next().unwrap();
-            (item.clone(), item.clone())
-        } else if elements.len() == 2 {
-            let mut iter = elements.into_iter();
-            (iter.next().unwrap(), iter.next().unwrap())
+        let centroid_bbox = prims.iter().fold(AABB::new_hidden(), |res, prim| {
+            surrounding_box(res, AABB::new(prim.centroid, prim.centroid))
+        });
+        let extent = centroid_bbox.max() - centroid_bbox.min();
+        let axis = if extent.0 >= extent.1 && extent.0 >= extent.2 {
+            0
+        } else if extent.1 >= extent.2 {
+            1
         } else {
-            let len = elements.len();
-            let mut iter = elements.into_iter();
-            let mut v_left = Vec::new();
-            let mut v_right = Vec::new();
+            2
+        };
 
-            for _ in 0..(len / 2) {
-                v_left.push(iter.next().unwrap());
-            }
-            for _ in (len / 2)..len {
-                v_right.push(iter.next().unwrap());
+        if axis_of(extent, axis) <= 0.0 {
+            // all centroids coincide, so no split can separate the primitives
+            if prims.len() <= MAX_LEAF_SIZE {
+                return self.leaf(bbox, prims);
             }
+            let right = prims.split_off(prims.len() / 2);
+            return self.inner(bbox, axis, prims, right, depth);
+        }
 
-            (
-                Rc::new(BVHNode::new(v_left, time_begin, time_end)) as Rc<dyn Hittable>,
-                Rc::new(BVHNode::new(v_right, time_begin, time_end)) as Rc<dyn Hittable>,
-            )
-        };
+        if depth >= MAX_SAH_DEPTH {
+            // median splits keep the tree shallow enough for the traversal stack
+            prims.sort_by(|p1, p2| {
+                axis_of(p1.centroid, axis).total_cmp(&axis_of(p2.centroid, axis))
+            });
+            let right = prims.split_off(prims.len() / 2);
+            return self.inner(bbox, axis, prims, right, depth);
+        }
+
+        let (split, cost) = best_split(&prims, &bbox, &centroid_bbox, axis);
+        if prims.len() <= MAX_LEAF_SIZE && cost >= prims.len() as f32 {
+            return self.leaf(bbox, prims);
+        }
+
+        let min = axis_of(centroid_bbox.min(), axis);
+        let (left, right): (Vec<_>, Vec<_>) = prims.into_iter().partition(|prim| {
+            bin_of(axis_of(prim.centroid, axis), min, axis_of(extent, axis)) <= split
+        });
+        self.inner(bbox, axis, left, right, depth)
+    }
+
+    fn leaf(&mut self, bbox: AABB, prims: Vec<Primitive>) -> usize {
+        self.nodes.push(LinearNode {
+            min: bbox.min(),
+            max: bbox.max(),
+            offset: self.elements.len() as u32,
+            count: prims.len() as u16,
+            axis: 0,
+        });
+        self.elements
+            .extend(prims.into_iter().map(|prim| prim.hittable));
+        self.nodes.len() - 1
+    }
+
+    fn inner(
+        &mut self,
+        bbox: AABB,
+        axis: usize,
+        left: Vec<Primitive>,
+        right: Vec<Primitive>,
+        depth: usize,
+    ) -> usize {
+        let idx = self.nodes.len();
+        self.nodes.push(LinearNode {
+            min: bbox.min(),
+            max: bbox.max(),
+            offset: 0,
+            count: 0,
+            axis: axis as u8,
+        });
+        self.build(left, depth + 1);
+        self.nodes[idx].offset = self.build(right, depth + 1) as u32;
+        idx
+    }
+}
 
-        let box_left = left.bounding_box(time_begin, time_end);
-        let box_right = right.bounding_box(time_begin, time_end);
-        let bbox = surrounding_box(box_left, box_right);
-        BVHNode { left, right, bbox }
+impl LinearNode {
+    /// Returns whether the ray hits the node's box within (t_min, t_max). It takes the
+    /// inverse of the ray direction, which is computed once for the whole traversal.
+    #[inline]
+    fn hit(&self, origin: Vec3, inv_dir: Vec3, t_min: f32, t_max: f32) -> bool {
+        let t0 = (self.min - origin) * inv_dir;
+        let t1 = (self.max - origin) * inv_dir;
+        let t_min = t_min
+            .max(t0.0.min(t1.0))
+            .max(t0.1.min(t1.1))
+            .max(t0.2.min(t1.2));
+        let t_max = t_max
+            .min(t0.0.max(t1.0))
+            .min(t0.1.max(t1.1))
+            .min(t0.2.max(t1.2));
+        t_min <= t_max
+    }
+}
+
+/// Returns the last bin of the left side for the cheapest split along the axis,
+/// together with the cost of that split relative to intersecting one primitive.
+fn best_split(prims: &[Primitive], bbox: &AABB, centroid_bbox: &AABB, axis: usize) -> (usize, f32) {
+    let min = axis_of(centroid_bbox.min(), axis);
+    let extent = axis_of(centroid_bbox.max(), axis) - min;
+
+    let mut counts = [0usize; SAH_BINS];
+    let mut boxes: Vec<AABB> = vec![AABB::new_hidden(); SAH_BINS];
+    for prim in prims {
+        let bin = bin_of(axis_of(prim.centroid, axis), min, extent);
+        counts[bin] += 1;
+        boxes[bin] = surrounding_box(boxes[bin].clone(), prim.bbox.clone());
+    }
+
+    // sweep from the right to get the area and count of every right side
+    let mut right_area = [0.0f32; SAH_BINS];
+    let mut right_count = [0usize; SAH_BINS];
+    let mut acc_box = AABB::new_hidden();
+    let mut acc_count = 0;
+    for bin in (1..SAH_BINS).rev() {
+        acc_box = surrounding_box(acc_box, boxes[bin].clone());
+        acc_count += counts[bin];
+        right_area[bin] = acc_box.surface_area();
+        right_count[bin] = acc_count;
+    }
+
+    let total_area = bbox.surface_area().max(f32::EPSILON);
+    let mut best = (0, f32::MAX);
+    let mut acc_box = AABB::new_hidden();
+    let mut acc_count = 0;
+    for split in 0..SAH_BINS - 1 {
+        acc_box = surrounding_box(acc_box, boxes[split].clone());
+        acc_count += counts[split];
+        if acc_count == 0 || right_count[split + 1] == 0 {
+            continue;
+        }
+
+        let cost = TRAVERSAL_COST
+            + (acc_box.surface_area() * acc_count as f32
+                + right_area[split + 1] * right_count[split + 1] as f32)
+                / total_area;
+        if cost < best.1 {
+            best = (split, cost);
+        }
+    }
+    best
+}
+
+#[inline]
+fn bin_of(value: f32, min: f32, extent: f32) -> usize {
+    (((value - min) / extent * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+}
+
+#[inline]
+fn axis_of(v: Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.0,
+        1 => v.1,
+        _ => v.2,
     }
 }
 
 impl Hittable for BVHNode {
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
-        if !self.bbox.hit(r, t_min, t_max) {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        if self.nodes.is_empty() {
             return None;
         }
 
-        match (self.left.hit(r, t_min, t_max), self.right.hit(r, t_min, t_max)) {
-            (None, None) => None,
-            (Some(left_hit), None) => Some(left_hit),
-            (None, Some(right_hit)) => Some(right_hit),
-            (Some(left_hit), Some(right_hit)) => {
-                if left_hit.t() < right_hit.t() {
-                    Some(left_hit)
+        let origin = r.origin();
+        let dir = r.direction();
+        let inv_dir = Vec3(1.0 / dir.0, 1.0 / dir.1, 1.0 / dir.2);
+        let dir_is_neg = [dir.0 < 0.0, dir.1 < 0.0, dir.2 < 0.0];
+
+        let mut closest_t = t_max;
+        let mut closest_hit = None;
+        let mut stack = [0usize; STACK_SIZE];
+        let mut stack_len = 0;
+        let mut idx = 0;
+        loop {
+            let node = &self.nodes[idx];
+            if node.hit(origin, inv_dir, t_min, closest_t) {
+                if node.count > 0 {
+                    let first = node.offset as usize;
+                    for elem in &self.elements[first..first + node.count as usize] {
+                        if let Some(hit) = elem.hit(r, t_min, closest_t) {
+                            closest_t = hit.t();
+                            closest_hit = Some(hit);
+                        }
+                    }
                 } else {
-                    Some(right_hit) 
+                    // visit the child closer to the ray origin first, so that hits
+                    // found there can cull the other one
+                    let (near, far) = if dir_is_neg[node.axis as usize] {
+                        (node.offset as usize, idx + 1)
+                    } else {
+                        (idx + 1, node.offset as usize)
+                    };
+                    stack[stack_len] = far;
+                    stack_len += 1;
+                    idx = near;
+                    continue;
                 }
             }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            idx = stack[stack_len];
         }
+
+        closest_hit
     }
 
     fn bounding_box(&self, _: f32, _: f32) -> AABB {
-        self.bbox.clone()
+        match self.nodes.first() {
+            Some(root) => AABB::new(root.min, root.max),
+            None => AABB::new_hidden(),
+        }
+    }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        collect_lights(&self.elements, lights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtl::{ScatterRecord, Scatterable};
+    use crate::obj::Sphere;
+
+    fn spheres(centers: &[Vec3]) -> Vec<Arc<dyn Hittable>> {
+        centers
+            .iter()
+            .map(|&c| Arc::new(Sphere::new(c, 0.5, TestMaterial {})) as Arc<dyn Hittable>)
+            .collect()
+    }
+
+    fn depth(bvh: &BVHNode, idx: usize) -> usize {
+        let node = &bvh.nodes[idx];
+        if node.count > 0 {
+            return 1;
+        }
+        1 + depth(bvh, idx + 1).max(depth(bvh, node.offset as usize))
+    }
+
+    #[test]
+    fn test_bvh_empty() {
+        let bvh = BVHNode::new(Vec::new(), 0.0, 1.0);
+        let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert!(bvh.hit(&ray, 0.0, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_bvh_closest_hit() {
+        let centers: Vec<Vec3> = (0..100)
+            .map(|i| Vec3(0.0, 0.0, -(i as f32) * 2.0 - 5.0))
+            .collect();
+        let bvh = BVHNode::new(spheres(&centers), 0.0, 1.0);
+
+        let ray = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        let hit = bvh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(hit.t(), 4.5);
+
+        let ray = Ray::new(Vec3(0.0, 0.0, -100.0), Vec3(0.0, 0.0, 1.0), 0.0);
+        let hit = bvh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(hit.t(), 0.5);
+
+        let ray = Ray::new(Vec3(0.0, 2.0, 0.0), Vec3(0.0, 0.0, -1.0), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_bvh_sah_structure() {
+        // two distant clusters are separated right at the root
+        let mut centers: Vec<Vec3> = (0..32).map(|i| Vec3(i as f32 * 0.01, 0.0, 0.0)).collect();
+        centers.extend((0..32).map(|i| Vec3(1000.0 + i as f32 * 0.01, 0.0, 0.0)));
+        let bvh = BVHNode::new(spheres(&centers), 0.0, 1.0);
+        let root = &bvh.nodes[0];
+        assert_eq!(root.count, 0);
+        assert!(bvh.nodes[1].max.0 < 10.0);
+        assert!(bvh.nodes[root.offset as usize].min.0 > 900.0);
+
+        // coincident primitives are still split into bounded leaves
+        let centers = vec![Vec3(1.0, 1.0, 1.0); 64];
+        let bvh = BVHNode::new(spheres(&centers), 0.0, 1.0);
+        let leaves: Vec<_> = bvh.nodes.iter().filter(|node| node.count > 0).collect();
+        assert_eq!(leaves.iter().map(|node| node.count).sum::<u16>(), 64);
+        assert!(leaves
+            .iter()
+            .all(|node| node.count as usize <= MAX_LEAF_SIZE));
+        assert!(depth(&bvh, 0) <= 7);
+    }
+
+    #[test]
+    fn test_bvh_deep_tree() {
+        // exponentially spaced spheres make every SAH split cut off a single sphere
+        let centers: Vec<Vec3> = (0..200).map(|i| Vec3(1.1f32.powi(i), 0.0, 0.0)).collect();
+        let bvh = BVHNode::new(spheres(&centers), 0.0, 1.0);
+        assert!(depth(&bvh, 0) <= STACK_SIZE);
+
+        let ray = Ray::new(Vec3(-10.0, 0.0, 0.0), Vec3(1.0, 0.0, 0.0), 0.0);
+        let hit = bvh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert!((hit.t() - 10.5).abs() < 1e-4);
+    }
+
+    struct TestMaterial {}
+
+    impl Scatterable for TestMaterial {
+        fn scatter(&self, _: &Ray, _: &Hit) -> Option<ScatterRecord> {
+            None
+        }
     }
 }
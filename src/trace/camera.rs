@@ -5,6 +5,7 @@ use crate::utils::rng::{rand_in_unit_disk, uniform_in_range};
 
 /// Camera represents an object that can be used to cast rays towards
 /// other objects. 
+#[derive(Clone)]
 pub struct Camera {
     origin: Vec3,
     low_left_corner: Vec3,
@@ -27,6 +28,7 @@ impl Camera {
     ///     aspect - the width/height ratio of the camera's view
     ///     aperture - controls how big is the output lens of the camera;
     ///                used for defocus blur effects
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         lookfrom: Vec3,
         lookat: Vec3,
@@ -61,6 +63,11 @@ impl Camera {
         }
     }
 
+    /// Returns the time interval during which the camera shutter is open.
+    pub fn shutter(&self) -> (f32, f32) {
+        (self.time_begin, self.time_end)
+    }
+
     /// Returns a ray that goes out of the camera towards point (s, t) on the
     /// output plane.
     pub fn get_ray(&self, s: f32, t: f32) -> Ray {
@@ -73,6 +80,3 @@ impl Camera {
         )
     }
 }
-
-// &Camera can be shared between threads.
-unsafe impl Sync for Camera {}
@@ -1,5 +1,7 @@
+use std::sync::Arc;
+
 use crate::math::Vec3;
-use crate::mtl::Scatterable;
+use crate::mtl::{ScatterRecord, Scatterable};
 use crate::obj::{surrounding_box, AABB};
 use crate::trace::Ray;
 
@@ -7,14 +9,20 @@ use crate::trace::Ray;
 /// It contains the following fields:
 ///     t - the ray function dimension where the hit occured
 ///     p - the ray function value vector where the hit occured
-///     n - the hit surface normal
+///     n - the hit surface normal, used for shading
+///     ng - the geometric normal of the surface, on the side of n, which tells
+///          which side of the surface a ray is on
 ///     mat - reference for the material that was hit
+///     outside_ior - index of refraction of the medium around the object that
+///                   was hit, which is 1 unless the integrator tracks media
 /// TODO: u, v
 pub struct Hit<'a> {
     t: f32,
     p: Vec3,
     n: Vec3,
+    ng: Vec3,
     mat: &'a dyn Scatterable,
+    outside_ior: f32,
 
     // for texture mapping
     u: f32,
@@ -23,8 +31,17 @@ pub struct Hit<'a> {
 
 impl<'a> Hit<'a> {
     /// Returns a new hit for the given parameters.
-    pub fn new(t: f32, p: Vec3, n: Vec3, mat: &'a dyn Scatterable, u: f32, v: f32) -> Hit {
-        Hit { t, p, n, mat, u, v }
+    pub fn new(t: f32, p: Vec3, n: Vec3, mat: &'a dyn Scatterable, u: f32, v: f32) -> Hit<'a> {
+        Hit {
+            t,
+            p,
+            n,
+            ng: n,
+            mat,
+            outside_ior: 1.0,
+            u,
+            v,
+        }
     }
 
     /// Accessor for hit.t.
@@ -42,6 +59,20 @@ impl<'a> Hit<'a> {
         self.n
     }
 
+    /// Accessor for hit.ng.
+    pub fn ng(&self) -> Vec3 {
+        self.ng
+    }
+
+    /// Sets the normal used for shading, e.g. interpolated between the
+    /// vertices of a mesh. The geometric normal is turned to its side.
+    pub fn set_shading_normal(&mut self, n: Vec3) {
+        if self.ng.dot(n) < 0.0 {
+            self.ng = -self.ng;
+        }
+        self.n = n;
+    }
+
     /// Accessor for hit.u.
     pub fn u(&self) -> f32 {
         self.u
@@ -52,36 +83,121 @@ impl<'a> Hit<'a> {
         self.v
     }
 
+    /// Accessor for hit.outside_ior.
+    pub fn outside_ior(&self) -> f32 {
+        self.outside_ior
+    }
+
+    /// Sets the index of refraction of the medium around the object that was
+    /// hit, for interfaces between two dielectrics.
+    pub fn set_outside_ior(&mut self, ior: f32) {
+        self.outside_ior = ior;
+    }
+
     /// Accessor for hit.mat.
     pub fn mat_ref(&self) -> &'a dyn Scatterable {
         self.mat
     }
 
     /// Scatter is a proxy function for the hit material.
-    /// It consumes the hit.
-    pub fn scatter(self, r: &Ray) -> Option<(Ray, Vec3)> {
+    pub fn scatter(&self, r: &Ray) -> Option<ScatterRecord> {
         self.mat.scatter(r, self)
     }
 }
 
 /// Hittable is a trait for objects that could be hit.
-pub trait Hittable {
+/// Objects are shared between the render threads, so they must be Send and Sync.
+pub trait Hittable: Send + Sync {
     /// Hit returns whether the passed ray hits the object within the given
     /// limits for t.
     /// Returns None if no hit occurs.
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit>;
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>>;
     /// Returns the bounding box for the object.
     fn bounding_box(&self, t_min: f32, t_max: f32) -> AABB;
+
+    /// Returns whether the object is a single emitting surface that can be
+    /// sampled with random and pdf_value.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Returns a random direction from origin towards a point of the object,
+    /// for objects that can be sampled. The direction is not normalized.
+    fn random(&self, _origin: Vec3, _time: f32) -> Vec3 {
+        Vec3(1.0, 0.0, 0.0)
+    }
+
+    /// Returns the density, with respect to solid angle, with which random
+    /// returns the direction of the ray from its origin. It is 0 when the ray
+    /// misses the object or the object can not be sampled.
+    fn pdf_value(&self, _r: &Ray) -> f32 {
+        0.0
+    }
+
+    /// Returns an estimate of the power emitted by an object that can be
+    /// sampled, which is used to choose between light sources.
+    fn power(&self) -> f32 {
+        0.0
+    }
+
+    /// Appends the emissive objects contained in this object to lights.
+    /// Objects that hold other objects forward the call to them.
+    fn lights(&self, _lights: &mut Vec<Arc<dyn Hittable>>) {}
+}
+
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        self.as_ref().hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, t_min: f32, t_max: f32) -> AABB {
+        self.as_ref().bounding_box(t_min, t_max)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.as_ref().is_emissive()
+    }
+
+    fn random(&self, origin: Vec3, time: f32) -> Vec3 {
+        self.as_ref().random(origin, time)
+    }
+
+    fn pdf_value(&self, r: &Ray) -> f32 {
+        self.as_ref().pdf_value(r)
+    }
+
+    fn power(&self) -> f32 {
+        self.as_ref().power()
+    }
+
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        self.as_ref().lights(lights)
+    }
+}
+
+/// Appends the emissive objects among elements to lights and collects the
+/// emissive objects contained in the others.
+pub(crate) fn collect_lights(
+    elements: &[Arc<dyn Hittable>],
+    lights: &mut Vec<Arc<dyn Hittable>>,
+) {
+    for elem in elements {
+        if elem.is_emissive() {
+            lights.push(Arc::clone(elem));
+        } else {
+            elem.lights(lights);
+        }
+    }
 }
 
 /// HitVec is a structure for holding multiple hittable objects.
 pub struct HitVec {
-    elements: Vec<Box<dyn Hittable>>,
+    elements: Vec<Arc<dyn Hittable>>,
 }
 
 impl HitVec {
     /// Creates a new HitVec for the given hittables.
-    pub fn new(elements: Vec<Box<dyn Hittable>>) -> HitVec {
+    pub fn new(elements: Vec<Arc<dyn Hittable>>) -> HitVec {
         HitVec { elements }
     }
 }
@@ -89,7 +205,7 @@ impl HitVec {
 impl Hittable for HitVec {
     /// Hit implements Hittable and returns the closest hit from
     /// the elements of HitVec.
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
         let mut closest_t = t_max;
         let mut last_hit = None;
         for elem in &self.elements {
@@ -110,8 +226,8 @@ impl Hittable for HitVec {
             }
         )
     }
-}
 
-// &HitVec can be shared between threads, as it can't be mutated after construction.
-unsafe impl Send for HitVec {}
-unsafe impl Sync for HitVec {}
+    fn lights(&self, lights: &mut Vec<Arc<dyn Hittable>>) {
+        collect_lights(&self.elements, lights);
+    }
+}
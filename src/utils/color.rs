@@ -0,0 +1,88 @@
+use crate::math::Vec3;
+
+// second radiation constant h * c / k in nm * K
+const C2: f64 = 1.4388e7;
+// wavelengths in nm over which the spectrum of a blackbody is integrated
+const LAMBDA_MIN: f64 = 380.0;
+const LAMBDA_MAX: f64 = 780.0;
+const LAMBDA_STEPS: usize = 80;
+
+/// Returns the linear sRGB color for the CIE XYZ color. Colors outside of the
+/// sRGB gamut get negative components.
+pub fn xyz_to_rgb(xyz: Vec3) -> Vec3 {
+    let Vec3(x, y, z) = xyz;
+    Vec3(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+/// Returns the linear sRGB color of the light of a blackbody at the given
+/// temperature in Kelvin, scaled to a luminance of 1. It is black for
+/// temperatures too low to glow visibly.
+pub fn blackbody(temperature: f32) -> Vec3 {
+    let t = f64::from(temperature);
+    if t <= 0.0 {
+        return Vec3(0.0, 0.0, 0.0);
+    }
+
+    // Planck's law, up to a constant factor, weighed by the color matching functions
+    let step = (LAMBDA_MAX - LAMBDA_MIN) / LAMBDA_STEPS as f64;
+    let mut xyz = [0.0f64; 3];
+    for i in 0..LAMBDA_STEPS {
+        let lambda = LAMBDA_MIN + (i as f64 + 0.5) * step;
+        let radiance = 1.0 / (lambda.powi(5) * ((C2 / (lambda * t)).exp() - 1.0));
+        let cmf = cie_cmf(lambda);
+        for c in 0..3 {
+            xyz[c] += radiance * cmf[c];
+        }
+    }
+    if xyz[1] <= 0.0 {
+        return Vec3(0.0, 0.0, 0.0);
+    }
+
+    let rgb = xyz_to_rgb(Vec3(
+        (xyz[0] / xyz[1]) as f32,
+        1.0,
+        (xyz[2] / xyz[1]) as f32,
+    ));
+    // very hot and very cold blackbodies are outside of the gamut
+    let rgb = Vec3(rgb.0.max(0.0), rgb.1.max(0.0), rgb.2.max(0.0));
+    rgb / rgb.luminance()
+}
+
+/// Returns the CIE 1931 color matching functions x̄, ȳ, z̄ at the wavelength in
+/// nm, with the multi-lobe fit of Wyman, Sloan and Shirley.
+fn cie_cmf(lambda: f64) -> [f64; 3] {
+    let g = |mu: f64, sigma1: f64, sigma2: f64| {
+        let sigma = if lambda < mu { sigma1 } else { sigma2 };
+        let t = (lambda - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    [
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blackbody() {
+        // the white point of sRGB is close to a blackbody at 6500 K
+        let white = blackbody(6500.0);
+        assert!((white.luminance() - 1.0).abs() < 1e-4);
+        assert!((white.0 - white.2).abs() < 0.1 && (white.1 - white.2).abs() < 0.1);
+
+        let candle = blackbody(1900.0);
+        assert!(candle.0 > candle.1 && candle.1 > candle.2);
+        let sky = blackbody(12000.0);
+        assert!(sky.2 > sky.1 && sky.1 > sky.0);
+
+        assert_eq!(blackbody(0.0), Vec3(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,154 @@
+/// Distribution1D is a piecewise constant distribution over [0, 1), given by
+/// the values of a non-negative function on equally sized intervals. If the
+/// function is 0 everywhere, the distribution is uniform.
+pub struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    /// Returns a new distribution proportional to the function values.
+    /// Panics if there are no values.
+    pub fn new(func: Vec<f32>) -> Distribution1D {
+        assert!(!func.is_empty());
+        let n = func.len() as f32;
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        cdf.push(0.0);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + f / n);
+        }
+
+        let integral = cdf[func.len()];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = if integral > 0.0 {
+                *c / integral
+            } else {
+                i as f32 / n
+            };
+        }
+        Distribution1D {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    /// Returns the integral of the function over [0, 1).
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    /// Returns a sample in [0, 1) for the uniform random number u, together
+    /// with its density and the index of the interval it falls into.
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let n = self.func.len();
+        let idx = (self.cdf.partition_point(|&c| c <= u).max(1) - 1).min(n - 1);
+
+        let width = self.cdf[idx + 1] - self.cdf[idx];
+        let du = if width > 0.0 {
+            (u - self.cdf[idx]) / width
+        } else {
+            0.0
+        };
+        let x = ((idx as f32 + du) / n as f32).min(1.0 - f32::EPSILON);
+        (x, self.pdf_at(idx), idx)
+    }
+
+    /// Returns the density of the distribution at x.
+    pub fn pdf(&self, x: f32) -> f32 {
+        let n = self.func.len();
+        let idx = ((x * n as f32).max(0.0) as usize).min(n - 1);
+        self.pdf_at(idx)
+    }
+
+    fn pdf_at(&self, idx: usize) -> f32 {
+        if self.integral > 0.0 {
+            self.func[idx] / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Distribution2D is a piecewise constant distribution over [0, 1)², given by
+/// the values of a non-negative function on a grid of equally sized cells.
+/// Samples are taken by choosing the row from the marginal distribution and
+/// then the column from the distribution of that row.
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Returns a new distribution for the function values of nu columns and
+    /// nv rows, stored row by row.
+    /// Panics if the number of values does not match.
+    pub fn new(func: &[f32], nu: usize, nv: usize) -> Distribution2D {
+        assert_eq!(func.len(), nu * nv);
+        let rows: Vec<_> = func
+            .chunks_exact(nu)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.integral()).collect());
+        Distribution2D { rows, marginal }
+    }
+
+    /// Returns a sample (u, v) in [0, 1)² for the uniform random numbers u1
+    /// and u2, together with its density.
+    pub fn sample(&self, u1: f32, u2: f32) -> ((f32, f32), f32) {
+        let (v, pdf_v, row) = self.marginal.sample(u2);
+        let (u, pdf_u, _) = self.rows[row].sample(u1);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    /// Returns the density of the distribution at (u, v).
+    pub fn pdf(&self, u: f32, v: f32) -> f32 {
+        let nv = self.rows.len();
+        let row = ((v * nv as f32).max(0.0) as usize).min(nv - 1);
+        self.marginal.pdf(v) * self.rows[row].pdf(u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_1d() {
+        let dist = Distribution1D::new(vec![0.0, 1.0, 3.0, 0.0]);
+        assert_eq!(dist.integral(), 1.0);
+        assert_eq!(dist.pdf(0.1), 0.0);
+        assert_eq!(dist.pdf(0.3), 1.0);
+        assert_eq!(dist.pdf(0.6), 3.0);
+
+        // a quarter of the samples falls into the second interval
+        let (x, pdf, idx) = dist.sample(0.125);
+        assert!((x - 0.375).abs() < 1e-6);
+        assert_eq!((pdf, idx), (1.0, 1));
+        let (x, pdf, idx) = dist.sample(0.625);
+        assert!((x - 0.625).abs() < 1e-6);
+        assert_eq!((pdf, idx), (3.0, 2));
+        assert_eq!(dist.sample(0.9999).2, 2);
+
+        let uniform = Distribution1D::new(vec![0.0, 0.0]);
+        assert_eq!(uniform.sample(0.75), (0.75, 1.0, 1));
+    }
+
+    #[test]
+    fn test_distribution_2d() {
+        // in the bottom row, only the right cell has any weight
+        let dist = Distribution2D::new(&[1.0, 1.0, 0.0, 2.0], 2, 2);
+        let ((u, v), pdf) = dist.sample(0.9, 0.9);
+        assert!(u >= 0.5 && v >= 0.5);
+        assert!((pdf - dist.pdf(u, v)).abs() < 1e-6);
+        assert_eq!(dist.pdf(0.25, 0.75), 0.0);
+
+        // the density integrates to 1
+        let total: f32 = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+            .iter()
+            .map(|&(u, v)| dist.pdf(u, v) / 4.0)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+}
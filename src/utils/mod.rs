@@ -1 +1,3 @@
+pub mod color;
+pub mod distribution;
 pub mod rng;
@@ -1,16 +1,36 @@
-use rand::{distributions::uniform::SampleUniform, Rng};
+use std::cell::RefCell;
+use std::f32::consts::PI;
+
+use rand::rngs::StdRng;
+use rand::{distributions::uniform::SampleUniform, Rng, SeedableRng};
 
 use crate::math::Vec3;
 
+thread_local! {
+    // Each thread owns a generator, so that seeding one thread does not
+    // affect the sequences of the others.
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseeds the random generator of the current thread. The numbers returned
+/// afterwards on this thread are fully determined by the seed.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
 /// Returns uniformly distributed random number from the interval [low, high).
 pub fn uniform_in_range<T: SampleUniform>(low: T, high: T) -> T {
-    rand::thread_rng().gen_range(low, high)
+    RNG.with(|rng| rng.borrow_mut().gen_range(low, high))
 }
 
 /// Returns random vector within a disk.
 pub fn rand_in_unit_disk() -> Vec3 {
     loop {
-        let v = Vec3(uniform_in_range(0.0, 1.0), uniform_in_range(0.0, 1.0), 0.0);
+        let v = Vec3(
+            uniform_in_range(-1.0, 1.0),
+            uniform_in_range(-1.0, 1.0),
+            0.0,
+        );
         if v.sq_len() < 1.0 {
             break v;
         }
@@ -21,12 +41,107 @@ pub fn rand_in_unit_disk() -> Vec3 {
 pub fn rand_in_unit_sphere() -> Vec3 {
     loop {
         let v = Vec3(
-            uniform_in_range(0.0, 1.0),
-            uniform_in_range(0.0, 1.0),
-            uniform_in_range(0.0, 1.0),
+            uniform_in_range(-1.0, 1.0),
+            uniform_in_range(-1.0, 1.0),
+            uniform_in_range(-1.0, 1.0),
         );
         if v.sq_len() < 1.0 {
             break v;
         }
     }
 }
+
+/// Returns random vector on the surface of a sphere.
+pub fn rand_unit_vector() -> Vec3 {
+    let z: f32 = uniform_in_range(-1.0, 1.0);
+    let phi = 2.0 * PI * uniform_in_range(0.0, 1.0);
+    let r = (1.0 - z * z).sqrt();
+    Vec3(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Returns random unit vector around the z axis, with density cos(theta) / pi
+/// for its angle theta to the z axis.
+pub fn rand_cosine_direction() -> Vec3 {
+    let r1: f32 = uniform_in_range(0.0, 1.0);
+    let r2: f32 = uniform_in_range(0.0, 1.0);
+    let phi = 2.0 * PI * r1;
+    Vec3(
+        phi.cos() * r2.sqrt(),
+        phi.sin() * r2.sqrt(),
+        (1.0 - r2).sqrt(),
+    )
+}
+
+/// Returns random unit vector around the z axis, pointing towards a sphere with
+/// the given radius at the given square distance on the z axis. The vectors are
+/// uniformly distributed over the solid angle of the sphere.
+pub fn rand_to_sphere(radius: f32, sq_dist: f32) -> Vec3 {
+    let r1: f32 = uniform_in_range(0.0, 1.0);
+    let r2: f32 = uniform_in_range(0.0, 1.0);
+    let cos_theta_max = (1.0 - radius * radius / sq_dist).max(0.0).sqrt();
+    let z = 1.0 + r2 * (cos_theta_max - 1.0);
+    let phi = 2.0 * PI * r1;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    Vec3(phi.cos() * r, phi.sin() * r, z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        seed(42);
+        let first: Vec<f32> = (0..8).map(|_| uniform_in_range(0.0, 1.0)).collect();
+        seed(42);
+        let second: Vec<f32> = (0..8).map(|_| uniform_in_range(0.0, 1.0)).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_rand_in_unit_disk() {
+        let n = 20000;
+        let mean = (0..n).fold(Vec3(0.0, 0.0, 0.0), |acc, _| {
+            let v = rand_in_unit_disk();
+            assert!(v.sq_len() < 1.0 && v.2 == 0.0);
+            acc + v / n as f32
+        });
+        assert!(mean.len() < 0.02);
+    }
+
+    #[test]
+    fn test_rand_in_unit_sphere() {
+        let n = 20000;
+        let mean = (0..n).fold(Vec3(0.0, 0.0, 0.0), |acc, _| {
+            let v = rand_in_unit_sphere();
+            assert!(v.sq_len() < 1.0);
+            acc + v / n as f32
+        });
+        assert!(mean.len() < 0.02);
+    }
+
+    #[test]
+    fn test_rand_cosine_direction() {
+        let n = 20000;
+        let mut mean_cos = 0.0;
+        for _ in 0..n {
+            let v = rand_cosine_direction();
+            assert!((v.len() - 1.0).abs() < 1e-4);
+            assert!(v.2 >= 0.0);
+            mean_cos += v.2 / n as f32;
+        }
+        // E[cos] = 2/3 for the cosine distribution
+        assert!((mean_cos - 2.0 / 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rand_to_sphere() {
+        // the sphere is seen under 30 degrees from the distance 2
+        let cos_theta_max = 30.0f32.to_radians().cos();
+        for _ in 0..1000 {
+            let v = rand_to_sphere(1.0, 4.0);
+            assert!((v.len() - 1.0).abs() < 1e-4);
+            assert!(v.2 >= cos_theta_max - 1e-5);
+        }
+    }
+}